@@ -1,55 +1,405 @@
-use std::rc::Rc;
+use std::{cell::RefCell, collections::VecDeque, path::{Path, PathBuf}, rc::Rc, thread, time::{Duration, Instant}};
 
+use image::RgbaImage;
 use glutin::{context::PossiblyCurrentContext, surface::{GlSurface, Surface, WindowSurface}};
-use imgui_layer::ImguiLayer;
+use imgui_layer::{ImguiLayer, ImguiViewports};
 use winit::{
     event::{ElementState, Event as WinitEvent, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent}, 
     event_loop::{ControlFlow, EventLoop}, 
-    keyboard::PhysicalKey, 
+    keyboard::{KeyCode, PhysicalKey}, 
     window::Window
 };
 use std::num::NonZeroU32;
 
 use crate::{
-    events::{Event, EventType}, layers::*, logger::*, window::WindowSystem
+    console::{self, builtins, Console}, events::{Event, EventType}, layers::*, logger::{self, *}, math::Vec2,
+    renderer::{
+        capture, Framebuffer, FramebufferSpec, HeadlessContext, OpenGLRendererAPI, RenderCommand, Renderer2D, RendererAPI, ShaderLibrary, TextureLibrary
+    },
+    window::{FullscreenMode, MonitorInfo, WindowData, WindowSystem}
 };
 
+// Key that takes a screenshot in the window loop
+const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+const SCREENSHOT_DIR: &str = "screenshots";
+
+// GL objects shared by the layers, with a window or headless
+struct GraphicsState {
+    render_command: RenderCommand,
+    shader_library: Rc<RefCell<ShaderLibrary>>,
+    texture_library: Rc<RefCell<TextureLibrary>>,
+    renderer_2d: Rc<RefCell<Renderer2D>>
+}
+
+impl GraphicsState {
+    fn new(render_command: RenderCommand) -> Result<GraphicsState, String> {
+        Ok(GraphicsState {
+            shader_library: Rc::new(RefCell::new(ShaderLibrary::new(render_command.clone()))),
+            texture_library: Rc::new(RefCell::new(TextureLibrary::new(render_command.clone()))),
+            renderer_2d: Rc::new(RefCell::new(Renderer2D::new(render_command.clone())?)),
+            render_command
+        })
+    }
+}
+
+// Everything that only exists when the application owns an on-screen window
+struct WindowState {
+    window: Rc<Window>,
+    surface: Surface<WindowSurface>,
+    context: PossiblyCurrentContext,
+    window_data: WindowData,
+    viewports: ImguiViewports
+}
+
+// The frames of a headless application with graphics are drawn into the target
+struct OffscreenState {
+    target: Framebuffer,
+    // Last field, the context is destroyed after the target
+    _context: HeadlessContext
+}
+
+pub struct HeadlessGraphics {
+    // Size of the frames, the layers get it in a WindowResize event
+    pub width: u32,
+    pub height: u32,
+    // Only uses software devices like Mesa llvmpipe, which draw the same pixels on every machine
+    pub software_only: bool,
+    // Debug GL context, the driver messages go to the logger
+    pub debug_context: bool
+}
+
+impl Default for HeadlessGraphics {
+    fn default() -> HeadlessGraphics {
+        HeadlessGraphics { width: 1280, height: 720, software_only: true, debug_context: cfg!(debug_assertions) }
+    }
+}
+
+struct ScreenshotRequest {
+    include_ui: bool,
+    // Timestamped name in the screenshots directory when there is none
+    path: Option<PathBuf>
+}
+
+pub struct HeadlessSettings {
+    // Stop after this many frames, run until a WindowClose event otherwise
+    pub max_frames: Option<u64>,
+    // Minimum duration of a frame, none runs the frames back to back
+    pub frame_time: Option<Duration>
+}
+
+impl Default for HeadlessSettings {
+    fn default() -> HeadlessSettings {
+        HeadlessSettings { max_frames: None, frame_time: Some(Duration::from_secs_f64(1.0 / 60.0)) }
+    }
+}
+
 pub struct Application {
     running: bool,
     layer_stack: LayerStack,
-    window: Rc<Window>,
-    surface: Surface<WindowSurface>,
-    context: PossiblyCurrentContext
+    event_queue: VecDeque<EventType<'static>>,
+    console: Rc<RefCell<Console>>,
+    frame_count: u64,
+    screenshot: Option<ScreenshotRequest>,
+    // The GL objects are freed before the window or headless context that owns them
+    graphics: Option<GraphicsState>,
+    window_state: Option<WindowState>,
+    offscreen: Option<OffscreenState>
 }
 
 impl Application {
     pub fn new () -> (Application, EventLoop<()>) {
+        Self::new_with_window(WindowData::default())
+    }
+
+    pub fn new_with_window(mut window_data: WindowData) -> (Application, EventLoop<()>) {
         // Layers settings
         let layer_stack = LayerStack::new();
 
         // Winit window settings
         let event_loop = EventLoop::new().unwrap();
-        let (window, surface, context) = WindowSystem::init_window(&event_loop);
+        let (window, surface, context) = WindowSystem::init_window(&event_loop, &mut window_data);
+
+        let console = Rc::new(RefCell::new(Console::new()));
+        builtins::register(&mut console.borrow_mut());
+
+        // Single GL context shared by the renderer, imgui and the game layers
+        let render_command = Rc::new(OpenGLRendererAPI::new(&context));
+        let size = window.inner_size();
+        render_command.set_viewport(0, 0, size.width, size.height);
+        render_command.set_window_size(size.width, size.height);
+        let graphics = GraphicsState::new(render_command).expect("Failed to create the renderer");
+        let viewports = ImguiViewports::default();
 
         // Create layers that compound the application
-        let imgui_layer = ImguiLayer::new(&window, &context);
+        let imgui_layer = ImguiLayer::new(
+            &window, graphics.render_command.clone(), console.clone(), graphics.shader_library.clone(), graphics.texture_library.clone(), viewports.clone(), graphics.renderer_2d.clone()
+        );
         
-        let mut app = Application { running: true, layer_stack, window: Rc::new(window), surface, context};
+        let window_state = WindowState { window: Rc::new(window), surface, context, window_data, viewports };
+        let mut app = Application {
+            running: true, layer_stack, event_queue: VecDeque::new(), console, frame_count: 0, screenshot: None,
+            graphics: Some(graphics), window_state: Some(window_state), offscreen: None
+        };
 
-        app.push_layer(Box::new(imgui_layer), LayerParam::None);
+        // Overlay, so imgui sees the input before the game layers and can keep it
+        app.push_overlay(Box::new(imgui_layer), LayerParam::None);
 
         (app, event_loop)
     }
 
+    // Application without window or graphics context, only runs the layers and the events
+    pub fn new_headless() -> Application {
+        hds_core_info!("Creating headless application");
+        set_crash_info("GL", "None, headless application");
+
+        let console = Rc::new(RefCell::new(Console::new()));
+        builtins::register(&mut console.borrow_mut());
+
+        Application {
+            running: true, layer_stack: LayerStack::new(), event_queue: VecDeque::new(), console, frame_count: 0, screenshot: None,
+            graphics: None, window_state: None, offscreen: None
+        }
+    }
+
+    // Headless application that renders its frames into an offscreen framebuffer, without window or display server
+    pub fn new_headless_with_graphics(settings: HeadlessGraphics) -> Result<Application, String> {
+        let mut app = Self::new_headless();
+
+        let (context, render_command) = HeadlessContext::new(settings.software_only, settings.debug_context)?;
+        let render_command = Rc::new(render_command);
+        render_command.set_window_size(settings.width, settings.height);
+
+        let spec = FramebufferSpec { width: settings.width, height: settings.height, follow_window: false, ..FramebufferSpec::default() };
+        let target = Framebuffer::new(&render_command, spec)?;
+
+        app.graphics = Some(GraphicsState::new(render_command)?);
+        app.offscreen = Some(OffscreenState { target, _context: context });
+
+        // The layers learn the size of the frames like the size of a window
+        app.queue_event(EventType::WindowResize { width: settings.width, height: settings.height });
+
+        Ok(app)
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.window_state.is_none()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    // Stops the application at the start of the next frame
+    pub fn close(&mut self) {
+        self.queue_event(EventType::WindowClose);
+    }
+
+    pub fn get_console(&self) -> Rc<RefCell<Console>> {
+        self.console.clone()
+    }
+
+    // Renderer of the application, none in headless applications without graphics
+    pub fn get_render_command(&self) -> Option<RenderCommand> {
+        self.graphics.as_ref().map(|graphics| graphics.render_command.clone())
+    }
+
+    // Shaders of the application, none in headless applications without graphics
+    pub fn get_shader_library(&self) -> Option<Rc<RefCell<ShaderLibrary>>> {
+        self.graphics.as_ref().map(|graphics| graphics.shader_library.clone())
+    }
+
+    // Textures of the application, none in headless applications without graphics
+    pub fn get_texture_library(&self) -> Option<Rc<RefCell<TextureLibrary>>> {
+        self.graphics.as_ref().map(|graphics| graphics.texture_library.clone())
+    }
+
+    // Batched 2D renderer of the application, none in headless applications without graphics
+    pub fn get_renderer_2d(&self) -> Option<Rc<RefCell<Renderer2D>>> {
+        self.graphics.as_ref().map(|graphics| graphics.renderer_2d.clone())
+    }
+
+    // Screenshot functions
+
+    // Saves the next frame in the screenshots directory, the UI is only drawn by the window loop
+    pub fn request_screenshot(&mut self, include_ui: bool) {
+        self.screenshot = Some(ScreenshotRequest { include_ui, path: None });
+    }
+
+    pub fn request_screenshot_to(&mut self, path: &Path, include_ui: bool) {
+        self.screenshot = Some(ScreenshotRequest { include_ui, path: Some(path.to_path_buf()) });
+    }
+
+    // Whether the screenshots of F12 and the console include imgui, from the r_screenshot_ui variable
+    pub fn is_screenshot_ui(&self) -> bool {
+        self.console.borrow().get_cvar("r_screenshot_ui").and_then(|cvar| cvar.get_value().as_bool()).unwrap_or(true)
+    }
+
+    // Colors of the last frame of a headless application with graphics
+    pub fn capture_offscreen_frame(&self) -> Option<Result<RgbaImage, String>> {
+        self.offscreen.as_ref().map(|offscreen| offscreen.target.capture(0))
+    }
+
+    // Reads the frame being drawn and saves it, called before the buffers are swapped
+    fn take_screenshot(&mut self) {
+        let request = match self.screenshot.take() {
+            Some(request) => request,
+            None => return,
+        };
+
+        let image = match (&self.graphics, &self.window_state, &self.offscreen) {
+            (Some(graphics), Some(state), _) => {
+                let size = state.window.inner_size();
+                Ok(capture::capture_window(&graphics.render_command, size.width, size.height))
+            },
+            (_, _, Some(offscreen)) => offscreen.target.capture(0),
+            _ => Err("There are no frames in a headless application without graphics".to_string()),
+        };
+
+        let path = request.path.unwrap_or_else(|| capture::screenshot_path(Path::new(SCREENSHOT_DIR)));
+        match image.and_then(|image| capture::save_png(&image, &path)) {
+            Ok(()) => hds_core_info!("Screenshot saved to {}", path.display()),
+            Err(err) => hds_core_error!("Failed to take screenshot: {}", err),
+        }
+    }
+
+    // Shows a color attachment of the framebuffer in an imgui window while the framebuffer lives
+    pub fn add_viewport(&mut self, title: &str, framebuffer: &Rc<RefCell<Framebuffer>>, attachment: usize) {
+        match &self.window_state {
+            Some(state) => state.viewports.borrow_mut().push((title.to_string(), Rc::downgrade(framebuffer), attachment)),
+            None => hds_core_warn!("Viewports are not available in a headless application"),
+        }
+    }
+
+    // Window functions
+    pub fn get_monitors(&self) -> Vec<MonitorInfo> {
+        match &self.window_state {
+            Some(state) => WindowSystem::get_monitors(&state.window),
+            None => vec![],
+        }
+    }
+
+    pub fn set_vsync(&mut self, enabled: bool) {
+        if let Some(state) = &mut self.window_state {
+            state.window_data.set_vsync(enabled);
+            WindowSystem::apply_vsync(&state.surface, &state.context, enabled);
+        }
+    }
+
+    pub fn is_vsync(&self) -> bool {
+        self.window_state.as_ref().is_some_and(|state| state.window_data.is_vsync())
+    }
+
+    pub fn set_fullscreen(&mut self, mode: FullscreenMode) {
+        match &mut self.window_state {
+            Some(state) => WindowSystem::set_fullscreen(&state.window, &mut state.window_data, mode),
+            None => hds_core_warn!("Fullscreen is not available in a headless application"),
+        }
+    }
+
     // Layers functions
     pub fn push_layer(&mut self, mut layer: Box<dyn Layer>, param: LayerParam) {
         layer.on_attach(param);
         self.layer_stack.push_layer(layer);
+        self.update_layers_crash_info();
     }
 
     pub fn push_overlay(&mut self, mut overlay: Box<dyn Layer>, param: LayerParam) {
         overlay.on_attach(param);
         self.layer_stack.push_overlay(overlay);
+        self.update_layers_crash_info();
+    }
+
+    fn update_layers_crash_info(&self) {
+        let layers: Vec<String> = self.layer_stack.get_names().into_iter()
+            .map(|name| if self.layer_stack.is_enabled(name) { name.to_string() } else { format!("{} (disabled)", name) })
+            .collect();
+
+        set_crash_info("Layers", layers.join(", "));
+    }
+
+    pub fn get_layer_names(&self) -> Vec<String> {
+        self.layer_stack.get_names().into_iter().map(str::to_string).collect()
+    }
+
+    pub fn is_layer_enabled(&self, name: &str) -> bool {
+        self.layer_stack.is_enabled(name)
+    }
+
+    // Disabled layers stay in the stack but don't get updates or events, false if the layer doesn't exist
+    pub fn set_layer_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let found = self.layer_stack.set_enabled(name, enabled);
+        self.update_layers_crash_info();
+        found
+    }
+
+    // Console functions
+    pub fn execute_command(&mut self, line: &str) {
+        self.run_command(line, false);
+    }
+
+    // Silent commands only print their errors
+    fn run_command(&mut self, line: &str, silent: bool) {
+        let mut words = line.split_whitespace();
+        let name = match words.next() {
+            Some(name) => name,
+            None => return,
+        };
+        let args: Vec<&str> = words.collect();
+
+        if !silent {
+            console::print(&format!("> {}", line));
+        }
+
+        let handler = self.console.borrow().get_handler(name);
+        if let Some(handler) = handler {
+            match handler(self, &args) {
+                Ok(message) if message.is_empty() || silent => (),
+                Ok(message) => console::print(&message),
+                Err(message) => console::print_error(&message),
+            }
+            return;
+        }
+
+        // Variables print their value without arguments and change it with them
+        let value = self.console.borrow().get_cvar(name).map(|cvar| format!("{} = {} ({})", name, cvar.get_value(), cvar.get_help()));
+        match value {
+            Some(value) if args.is_empty() => {
+                if !silent {
+                    console::print(&value);
+                }
+            },
+            Some(_) => {
+                let result = self.console.borrow_mut().set_cvar(name, &args.join(" "));
+                match result {
+                    Ok((value, Some(on_change))) => on_change(self, &value),
+                    Ok(_) => (),
+                    Err(message) => console::print_error(&message),
+                }
+            },
+            None => console::print_error(&format!("Unknown command {}", name)),
+        }
+    }
+
+    fn execute_console_commands(&mut self) {
+        let lines = self.console.borrow_mut().take_pending();
+        for pending in lines {
+            self.run_command(&pending.line, pending.silent);
+        }
+    }
+
+    // Events that are dispatched to the layers at the start of the next frame
+    pub fn queue_event(&mut self, event_type: EventType<'static>) {
+        self.event_queue.push_back(event_type);
+    }
+
+    fn dispatch_queued_events(&mut self) {
+        while let Some(event_type) = self.event_queue.pop_front() {
+            self.on_event(Event::new(event_type));
+
+            if let EventType::WindowClose = event_type {
+                self.on_windows_close();
+            }
+        }
     }
 
     // Application flow
@@ -60,29 +410,73 @@ impl Application {
     }
 
     pub fn main_loop(&mut self, event_loop: EventLoop<()>) {
-        let window = self.window.clone();
+        let window = self.window_state.as_ref()
+            .expect("Headless application has no event loop, use run_headless")
+            .window.clone();
 
         event_loop.run(move |event, elwt| {
+            // Layers like imgui read the events of the window system before they become hades events
+            self.on_event(Event::new(EventType::Winit(&event, &window)));
+
             // Handle the events from the window
             match event {
                 WinitEvent::NewEvents(_) => {
+                    self.begin_frame();
+
+                    if let Some(graphics) = &self.graphics {
+                        graphics.shader_library.borrow_mut().check_for_changes();
+                    }
+
                     let hades_event = Event::new(EventType::NewEvents);
                     self.on_event(hades_event);
+                    self.execute_console_commands();
+                    self.dispatch_queued_events();
                     self.on_update();
+
+                    if !self.running {
+                        elwt.exit();
+                    }
                 },
                 WinitEvent::AboutToWait => {
                     let hades_event = Event::new(EventType::AboutToWait(&window));
                     self.on_event(hades_event);
-                    self.window.request_redraw();
+                    window.request_redraw();
                 }
                 WinitEvent::WindowEvent { event, .. } => {
                     match event {
                         WindowEvent::RedrawRequested => {
+                            let render_command = self.get_render_command();
+
+                            if let Some(graphics) = &self.graphics {
+                                graphics.render_command.clear();
+                                graphics.renderer_2d.borrow_mut().reset_stats();
+                            }
+
+                            // The scene is drawn before the UI so the screenshots can leave the UI out
+                            if let Some(render_command) = &render_command { render_command.begin_gpu_timer("Scene") }
+                            self.on_event(Event::new(EventType::AppRender));
+                            if let Some(render_command) = &render_command { render_command.end_gpu_timer() }
+
+                            if self.screenshot.as_ref().is_some_and(|request| !request.include_ui) {
+                                self.take_screenshot();
+                            }
+
+                            if let Some(render_command) = &render_command { render_command.begin_gpu_timer("UI") }
                             let hades_event = Event::new(EventType::WindowRedrawRequest(&window));
                             self.on_event(hades_event);
+                            if let Some(render_command) = &render_command { render_command.end_gpu_timer() }
+
+                            // The back buffer is undefined after the swap
+                            self.take_screenshot();
+
+                            if let Some(state) = &self.window_state {
+                                state.surface.swap_buffers(&state.context)
+                                    .expect("Failed swap buffers");
+                            }
 
-                            self.surface.swap_buffers(&self.context)
-                                .expect("Failed swap buffers");
+                            if let Some(render_command) = render_command {
+                                render_command.end_frame();
+                            }
                         },
                         WindowEvent::CloseRequested => {
                             let hades_event = Event::new(EventType::WindowClose);
@@ -92,18 +486,37 @@ impl Application {
                             self.on_windows_close();
                         },
                         WindowEvent::Resized(size) => {
-                            if size.width > 0 && size.height > 0 {
-                                self.surface.resize(
-                                    &self.context, 
+                            if let (Some(state), true) = (&mut self.window_state, size.width > 0 && size.height > 0) {
+                                state.surface.resize(
+                                    &state.context, 
                                     NonZeroU32::new(size.width).unwrap(),
                                     NonZeroU32::new(size.height).unwrap(),
-                                )
+                                );
+
+                                WindowSystem::capture_state(&state.window, &mut state.window_data);
+                            }
+
+                            if let (Some(graphics), true) = (&self.graphics, size.width > 0 && size.height > 0) {
+                                graphics.render_command.set_viewport(0, 0, size.width, size.height);
+                                graphics.render_command.set_window_size(size.width, size.height);
                             }
 
                             let hades_event = Event::new(EventType::WindowResize { width: size.width, height: size.height });
                             self.on_event(hades_event);
                         },
+                        WindowEvent::Moved(_) => {
+                            if let Some(state) = &mut self.window_state {
+                                WindowSystem::capture_state(&state.window, &mut state.window_data);
+                            }
+
+                            let hades_event = Event::new(EventType::WindowMoved);
+                            self.on_event(hades_event);
+                        },
                         WindowEvent::KeyboardInput { event: KeyEvent { physical_key, state, repeat, ..}, .. } => {
+                            if physical_key == PhysicalKey::Code(SCREENSHOT_KEY) && state == ElementState::Pressed && !repeat {
+                                self.request_screenshot(self.is_screenshot_ui());
+                            }
+
                             let hades_event = Event::new(match physical_key {
                                 PhysicalKey::Code(keycode) => match state {
                                     ElementState::Pressed => if repeat {
@@ -135,33 +548,101 @@ impl Application {
                         },
                         WindowEvent::MouseWheel { delta, .. } => {
                             let hades_event = Event::new(match delta {
-                                MouseScrollDelta::LineDelta(x_offset, y_offset) => EventType::MouseScrolled { x_offset, y_offset },
+                                MouseScrollDelta::LineDelta(x_offset, y_offset) => EventType::MouseScrolled { offset: Vec2::new(x_offset, y_offset) },
                                 _ => EventType::None,
                             });
                             self.on_event(hades_event);
                         },
                         WindowEvent::CursorMoved { position, .. } => {
-                            let hades_event = Event::new(EventType::MouseMoved { x: position.x as f32, y: position.y as f32 });
+                            let hades_event = Event::new(EventType::MouseMoved { position: Vec2::new(position.x as f32, position.y as f32) });
                             self.on_event(hades_event);
                         },
                         _ => ()
                     }
                 },
+                // Every way out of the loop ends here, while the window still exists
+                WinitEvent::LoopExiting => self.shutdown(),
                 _ => ()
             }
         }).expect("Error with the events")
     }
 
+    // Same frame flow as the window loop but driven by a plain loop instead of winit
+    pub fn run_headless(&mut self, settings: HeadlessSettings) {
+        hds_core_info!("Running headless application");
+
+        let mut frame: u64 = 0;
+
+        while self.running && settings.max_frames.is_none_or(|max_frames| frame < max_frames) {
+            let frame_start = Instant::now();
+
+            self.begin_frame();
+            self.on_event(Event::new(EventType::NewEvents));
+            self.execute_console_commands();
+            self.dispatch_queued_events();
+            self.on_update();
+            self.render_offscreen();
+
+            frame += 1;
+
+            if let Some(frame_time) = settings.frame_time {
+                let elapsed = frame_start.elapsed();
+                if elapsed < frame_time {
+                    thread::sleep(frame_time - elapsed);
+                }
+            }
+        }
+
+        self.shutdown();
+        hds_core_info!(frames = frame; "Headless application stopped");
+    }
+
+    // Draws the layers into the target of a headless application with graphics
+    fn render_offscreen(&mut self) {
+        let render_command = match (&self.graphics, &mut self.offscreen) {
+            (Some(graphics), Some(offscreen)) => {
+                offscreen.target.bind();
+                graphics.render_command.clear();
+                graphics.renderer_2d.borrow_mut().reset_stats();
+                graphics.render_command.clone()
+            },
+            _ => return,
+        };
+
+        render_command.begin_gpu_timer("Scene");
+        self.on_event(Event::new(EventType::AppRender));
+        render_command.end_gpu_timer();
+
+        if let Some(offscreen) = &self.offscreen {
+            offscreen.target.unbind();
+        }
+
+        self.take_screenshot();
+        render_command.end_frame();
+    }
+
+    fn begin_frame(&mut self) {
+        self.frame_count += 1;
+        logger::set_frame(self.frame_count);
+    }
+
+    pub fn get_frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
     pub fn on_update(&mut self) {
-        for layer in self.layer_stack.get_layers().iter_mut() {
+        for layer in self.layer_stack.get_enabled_layers() {
             layer.on_update()
         }
     }
 
     pub fn on_event(&mut self, mut event: Event) {
-        hds_core_trace!("{}", event.to_string());
+        // The winit events come several times per frame and are already traced as the hades events they become
+        if !matches!(event.get_event_type(), EventType::Winit(..)) {
+            hds_core_trace!("{}", event);
+        }
 
-        for layer in self.layer_stack.get_layers().iter_mut().rev() {
+        for layer in self.layer_stack.get_enabled_layers().rev() {
             event.set_handled(layer.on_event(&event));
             if event.is_handled() {
                 break;
@@ -173,4 +654,19 @@ impl Application {
     fn on_windows_close(&mut self){
         self.running = false;
     }
+
+    // Saves the state kept between runs, shared by the window and the headless loops
+    fn shutdown(&mut self) {
+        if let Some(state) = &mut self.window_state {
+            WindowSystem::capture_state(&state.window, &mut state.window_data);
+
+            if let Err(err) = state.window_data.save() {
+                hds_core_error!("Failed to save window state: {}", err);
+            }
+        }
+
+        if let Err(err) = self.console.borrow().save() {
+            hds_core_error!("Failed to save console config: {}", err);
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,130 @@
+use log::LevelFilter;
+
+use crate::{application::Application, logger};
+
+use super::{CVarValue, Console};
+
+// Commands and variables that every hades application has
+pub fn register(console: &mut Console) {
+    console.register_command("help", "Lists the commands and variables, or describes one", |app, args| {
+        let console = app.get_console();
+        let console = console.borrow();
+
+        if let Some(name) = args.first() {
+            if let Some((_, help)) = console.get_commands().find(|(command, _)| command == name) {
+                return Ok(format!("{} - {}", name, help));
+            }
+
+            return match console.get_cvar(name) {
+                Some(cvar) => Ok(format!("{} = {} (default {}) - {}", name, cvar.get_value(), cvar.get_default(), cvar.get_help())),
+                None => Err(format!("Unknown command {}", name)),
+            };
+        }
+
+        let mut lines: Vec<String> = console.get_commands().map(|(name, help)| format!("{} - {}", name, help)).collect();
+        lines.extend(console.get_cvars().iter().map(|cvar| format!("{} = {} - {}", cvar.get_name(), cvar.get_value(), cvar.get_help())));
+
+        Ok(lines.join("\n"))
+    });
+
+    console.register_command("quit", "Closes the application", |app, _| {
+        app.close();
+        Ok(String::new())
+    });
+
+    console.register_command("layer.list", "Lists the layers of the application", |app, _| {
+        let names: Vec<String> = app.get_layer_names().into_iter()
+            .map(|name| {
+                let state = if app.is_layer_enabled(&name) { "enabled" } else { "disabled" };
+                format!("{} ({})", name, state)
+            })
+            .collect();
+
+        Ok(names.join("\n"))
+    });
+
+    console.register_command("layer.enable", "layer.enable <name>: Sends updates and events to the layer again", |app, args| {
+        set_layer_enabled(app, args, true)
+    });
+
+    console.register_command("layer.disable", "layer.disable <name>: Stops the updates and events of the layer", |app, args| {
+        set_layer_enabled(app, args, false)
+    });
+
+    console.register_command("log.level", "log.level <target> <level>: Changes the log level of a target or a module path like hades::renderer", |_, args| {
+        match args {
+            [target, level] => {
+                let level: LevelFilter = level.parse().map_err(|_| format!("Invalid log level {}", level))?;
+                logger::set_target_level(target, level);
+                Ok(format!("{} logs at {}", target, level))
+            },
+            _ => Err("Usage: log.level <target> <level>".to_string()),
+        }
+    });
+
+    console.register_command("shader.reload", "Compiles the shaders loaded from files again", |app, _| {
+        let shader_library = app.get_shader_library().ok_or("There are no shaders in a headless application")?;
+        let failed = shader_library.borrow_mut().reload_all();
+
+        if failed == 0 { Ok("Shaders reloaded".to_string()) } else { Err(format!("{} shaders failed to reload", failed)) }
+    });
+
+    console.register_command("screenshot", "screenshot [ui|noui]: Saves the next frame in the screenshots directory", |app, args| {
+        let include_ui = match args.first() {
+            None => app.is_screenshot_ui(),
+            Some(&"ui") => true,
+            Some(&"noui") => false,
+            Some(arg) => return Err(format!("Invalid argument {}, expected ui or noui", arg)),
+        };
+
+        app.request_screenshot(include_ui);
+        Ok(String::new())
+    });
+
+    console.register_cvar("r_screenshot_ui", CVarValue::Bool(true), "Includes the imgui windows in the screenshots");
+
+    console.register_cvar_with_callback("r_vsync", CVarValue::Bool(true), "Synchronizes the frames with the monitor refresh", |app, value| {
+        if let Some(enabled) = value.as_bool() {
+            app.set_vsync(enabled);
+        }
+    });
+
+    console.register_cvar_with_callback("r_shader_hot_reload", CVarValue::Bool(true), "Reloads the shaders when their files change", |app, value| {
+        if let (Some(shader_library), Some(enabled)) = (app.get_shader_library(), value.as_bool()) {
+            shader_library.borrow_mut().set_hot_reload(enabled);
+        }
+    });
+
+    console.register_cvar_with_callback("r_gl_error_checks", CVarValue::Bool(false), "Checks glGetError after every renderer call, debug builds only", |app, value| {
+        if let (Some(render_command), Some(enabled)) = (app.get_render_command(), value.as_bool()) {
+            render_command.set_error_checks(enabled);
+        }
+    });
+
+    console.register_cvar_with_validator(
+        "log_level",
+        // Starts at the level of the logger configuration, like HADES_LOG
+        CVarValue::Str(logger::get_filters().get_default().to_string().to_lowercase()),
+        "Log level of the targets without their own level",
+        |value| match value.as_str().map(str::parse::<LevelFilter>) {
+            Some(Ok(_)) => Ok(()),
+            _ => Err(format!("Invalid log level {}, expected off, error, warn, info, debug or trace", value)),
+        },
+        |_, value| {
+            if let Some(Ok(level)) = value.as_str().map(str::parse::<LevelFilter>) {
+                logger::set_default_level(level);
+            }
+        }
+    );
+}
+
+fn set_layer_enabled(app: &mut Application, args: &[&str], enabled: bool) -> Result<String, String> {
+    let name = args.first().ok_or("Missing the layer name")?;
+
+    if app.set_layer_enabled(name, enabled) {
+        Ok(format!("{} {}", name, if enabled { "enabled" } else { "disabled" }))
+    }
+    else {
+        Err(format!("There is no layer {}", name))
+    }
+}
@@ -0,0 +1,373 @@
+pub mod builtins;
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc
+};
+
+use crate::{application::Application, logger::*};
+
+// Runs a command with the arguments that follow its name, the message is printed in the console
+pub type CommandHandler = Rc<dyn Fn(&mut Application, &[&str]) -> Result<String, String>>;
+// Applies the new value of a console variable
+pub type CVarCallback = Rc<dyn Fn(&mut Application, &CVarValue)>;
+// Rejects the values a console variable can't take, the message is printed in the console
+pub type CVarValidator = Rc<dyn Fn(&CVarValue) -> Result<(), String>>;
+
+const HISTORY_SIZE: usize = 64;
+
+#[derive(Clone, PartialEq)]
+pub enum CVarValue {
+    Bool(bool),
+    Int(i64),
+    Float(f32),
+    Str(String)
+}
+
+impl CVarValue {
+    // Parses the text as a value of the same type as this one
+    pub fn parse_as(&self, text: &str) -> Result<CVarValue, String> {
+        match self {
+            CVarValue::Bool(_) => match text {
+                "1" | "true" | "on" => Ok(CVarValue::Bool(true)),
+                "0" | "false" | "off" => Ok(CVarValue::Bool(false)),
+                _ => Err(format!("Expected a boolean, got {}", text)),
+            },
+            CVarValue::Int(_) => text.parse().map(CVarValue::Int).map_err(|_| format!("Expected an integer, got {}", text)),
+            CVarValue::Float(_) => text.parse().map(CVarValue::Float).map_err(|_| format!("Expected a number, got {}", text)),
+            CVarValue::Str(_) => Ok(CVarValue::Str(text.to_string())),
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            CVarValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            CVarValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            CVarValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            CVarValue::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CVarValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CVarValue::Bool(value)  => write!(f, "{}", if *value { 1 } else { 0 }),
+            CVarValue::Int(value)   => write!(f, "{}", value),
+            CVarValue::Float(value) => write!(f, "{}", value),
+            CVarValue::Str(value)   => write!(f, "{}", value)
+        }
+    }
+}
+
+pub struct CVar {
+    name: String,
+    help: String,
+    value: CVarValue,
+    default: CVarValue,
+    validator: Option<CVarValidator>,
+    on_change: Option<CVarCallback>,
+    // Set by a command or the config file, only these are saved
+    modified: bool
+}
+
+impl CVar {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_help(&self) -> &str {
+        &self.help
+    }
+
+    pub fn get_value(&self) -> &CVarValue {
+        &self.value
+    }
+
+    pub fn get_default(&self) -> &CVarValue {
+        &self.default
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+}
+
+// Line waiting to be executed, the ones of the config file are not echoed
+pub(crate) struct PendingLine {
+    pub line: String,
+    pub silent: bool
+}
+
+struct Command {
+    name: String,
+    help: String,
+    handler: CommandHandler
+}
+
+// Registry of the commands and console variables, the lines typed in the console
+// are queued here and executed by the application at the start of the next frame
+pub struct Console {
+    commands: Vec<Command>,
+    cvars: Vec<CVar>,
+    history: VecDeque<String>,
+    pending: VecDeque<PendingLine>,
+    config_path: Option<PathBuf>
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Console {
+    pub fn new() -> Console {
+        Console { commands: vec![], cvars: vec![], history: VecDeque::new(), pending: VecDeque::new(), config_path: None }
+    }
+
+    pub fn register_command(&mut self, name: &str, help: &str, handler: impl Fn(&mut Application, &[&str]) -> Result<String, String> + 'static) {
+        if self.is_registered(name) {
+            hds_core_warn!("Console name {} is already registered", name);
+            return;
+        }
+
+        self.commands.push(Command { name: name.to_string(), help: help.to_string(), handler: Rc::new(handler) });
+    }
+
+    pub fn register_cvar(&mut self, name: &str, default: CVarValue, help: &str) {
+        self.add_cvar(name, default, help, None, None);
+    }
+
+    // Console variable that runs the callback every time its value changes
+    pub fn register_cvar_with_callback(&mut self, name: &str, default: CVarValue, help: &str, on_change: impl Fn(&mut Application, &CVarValue) + 'static) {
+        self.add_cvar(name, default, help, None, Some(Rc::new(on_change)));
+    }
+
+    // Same as register_cvar_with_callback but the values the validator rejects are never stored
+    pub fn register_cvar_with_validator(
+        &mut self,
+        name: &str,
+        default: CVarValue,
+        help: &str,
+        validator: impl Fn(&CVarValue) -> Result<(), String> + 'static,
+        on_change: impl Fn(&mut Application, &CVarValue) + 'static
+    ) {
+        self.add_cvar(name, default, help, Some(Rc::new(validator)), Some(Rc::new(on_change)));
+    }
+
+    fn add_cvar(&mut self, name: &str, default: CVarValue, help: &str, validator: Option<CVarValidator>, on_change: Option<CVarCallback>) {
+        if self.is_registered(name) {
+            hds_core_warn!("Console name {} is already registered", name);
+            return;
+        }
+
+        self.cvars.push(CVar { name: name.to_string(), help: help.to_string(), value: default.clone(), default, validator, on_change, modified: false });
+    }
+
+    fn is_registered(&self, name: &str) -> bool {
+        self.commands.iter().any(|command| command.name == name) || self.cvars.iter().any(|cvar| cvar.name == name)
+    }
+
+    pub fn get_cvar(&self, name: &str) -> Option<&CVar> {
+        self.cvars.iter().find(|cvar| cvar.name == name)
+    }
+
+    pub fn get_cvars(&self) -> &[CVar] {
+        &self.cvars
+    }
+
+    // Names and help texts of the commands
+    pub fn get_commands(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.commands.iter().map(|command| (command.name.as_str(), command.help.as_str()))
+    }
+
+    pub(crate) fn get_handler(&self, name: &str) -> Option<CommandHandler> {
+        self.commands.iter().find(|command| command.name == name).map(|command| command.handler.clone())
+    }
+
+    // Changes the value and returns the callback that must be run with it
+    pub(crate) fn set_cvar(&mut self, name: &str, text: &str) -> Result<(CVarValue, Option<CVarCallback>), String> {
+        let cvar = self.cvars.iter_mut()
+            .find(|cvar| cvar.name == name)
+            .ok_or_else(|| format!("Unknown console variable {}", name))?;
+
+        let value = cvar.value.parse_as(text)?;
+        if let Some(validator) = &cvar.validator {
+            validator(&value)?;
+        }
+
+        cvar.value = value;
+        cvar.modified = true;
+        Ok((cvar.value.clone(), cvar.on_change.clone()))
+    }
+
+    // Commands and variables that start with the text
+    pub fn complete(&self, prefix: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = self.commands.iter().map(|command| command.name.as_str())
+            .chain(self.cvars.iter().map(|cvar| cvar.name.as_str()))
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+
+        names.sort_unstable();
+        names
+    }
+
+    // Queues a line to be executed by the application
+    pub fn submit(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+
+        if self.history.back().map(String::as_str) != Some(line) {
+            if self.history.len() >= HISTORY_SIZE {
+                self.history.pop_front();
+            }
+
+            self.history.push_back(line.to_string());
+        }
+
+        self.pending.push_back(PendingLine { line: line.to_string(), silent: false });
+    }
+
+    pub fn get_history(&self) -> &VecDeque<String> {
+        &self.history
+    }
+
+    pub(crate) fn take_pending(&mut self) -> Vec<PendingLine> {
+        self.pending.drain(..).collect()
+    }
+
+    // Loads the saved console variables, they are applied as commands in the next frame
+    // without being echoed, only their errors are printed
+    pub fn load(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let lines = contents.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+                self.pending.extend(lines.map(|line| PendingLine { line: line.to_string(), silent: true }));
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => hds_core_warn!("Failed to read console config {}: {}", path.display(), err),
+        }
+
+        self.config_path = Some(path);
+    }
+
+    pub fn get_config_path(&self) -> Option<&Path> {
+        self.config_path.as_deref()
+    }
+
+    // Writes the console variables that were set to the config file given to load, the others
+    // keep following their default
+    pub fn save(&self) -> io::Result<()> {
+        let path = match &self.config_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let contents: String = self.cvars.iter()
+            .filter(|cvar| cvar.modified)
+            .map(|cvar| format!("{} {}\n", cvar.name, cvar.value))
+            .collect();
+
+        fs::write(path, contents)
+    }
+}
+
+// Output of the console goes through the logger so it shows up next to the logs
+pub fn print(message: &str) {
+    log::log!(target: "CONSOLE", log::Level::Info, "{}", message);
+}
+
+pub fn print_error(message: &str) {
+    log::log!(target: "CONSOLE", log::Level::Error, "{}", message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_as_keeps_the_type() {
+        let boolean = CVarValue::Bool(false);
+        assert!(boolean.parse_as("on") == Ok(CVarValue::Bool(true)));
+        assert!(boolean.parse_as("0") == Ok(CVarValue::Bool(false)));
+        assert!(boolean.parse_as("yes").is_err());
+
+        assert!(CVarValue::Int(0).parse_as("-12") == Ok(CVarValue::Int(-12)));
+        assert!(CVarValue::Int(0).parse_as("1.5").is_err());
+
+        assert!(CVarValue::Float(0.0).parse_as("1.5") == Ok(CVarValue::Float(1.5)));
+        assert!(CVarValue::Float(0.0).parse_as("fast").is_err());
+
+        assert!(CVarValue::Str(String::new()).parse_as("warn") == Ok(CVarValue::Str("warn".to_string())));
+    }
+
+    #[test]
+    fn save_only_writes_the_set_variables() {
+        let path = std::env::temp_dir().join(format!("hades_console_{}.cfg", std::process::id()));
+
+        let mut console = Console::new();
+        console.register_cvar("r_vsync", CVarValue::Bool(true), "");
+        console.register_cvar("log_level", CVarValue::Str("info".to_string()), "");
+        console.load(&path);
+        assert!(console.set_cvar("r_vsync", "0").is_ok());
+        assert!(console.set_cvar("log_level", "loud").is_ok());
+        console.save().unwrap();
+
+        let mut saved = Console::new();
+        saved.register_cvar("r_vsync", CVarValue::Bool(true), "");
+        saved.register_cvar("log_level", CVarValue::Str("info".to_string()), "");
+        saved.load(&path);
+        let lines: Vec<String> = saved.take_pending().into_iter().map(|pending| pending.line).collect();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(lines, ["r_vsync 0", "log_level loud"]);
+    }
+
+    #[test]
+    fn defaults_are_not_saved() {
+        let path = std::env::temp_dir().join(format!("hades_console_defaults_{}.cfg", std::process::id()));
+
+        let mut console = Console::new();
+        console.register_cvar("r_vsync", CVarValue::Bool(true), "");
+        console.load(&path);
+        console.save().unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert!(contents.is_empty());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for value in [CVarValue::Bool(true), CVarValue::Int(7), CVarValue::Float(0.25), CVarValue::Str("info".to_string())] {
+            assert!(value.parse_as(&value.to_string()) == Ok(value.clone()));
+        }
+    }
+}
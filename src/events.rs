@@ -1,5 +1,7 @@
 use std::fmt::{Display, self};
-use imgui_winit_support::winit::window::Window as WinitWindow;
+use imgui_winit_support::winit::{event::Event as WinitEvent, window::Window as WinitWindow};
+
+use crate::math::Vec2;
 
 #[derive(Copy, Clone)]
 pub enum EventType<'a> {
@@ -11,35 +13,38 @@ pub enum EventType<'a> {
     AppTick, AppUpdate, AppRender, 
     AboutToWait(&'a WinitWindow),
     NewEvents,
+    // Event of the window system before it becomes a hades event, for the layers like imgui that read them directly
+    Winit(&'a WinitEvent<()>, &'a WinitWindow),
     KeyPressed { keycode: i32, repeat_count: i32 }, 
     KeyReleased { keycode: i32 },
     MouseButtonPressed { button: i32 }, 
     MouseButtonReleased { button: i32 },
-    MouseMoved { x: f32, y: f32 }, 
-    MouseScrolled { x_offset: f32, y_offset: f32 }
+    MouseMoved { position: Vec2 }, 
+    MouseScrolled { offset: Vec2 }
 }
 
-impl Display for EventType<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl EventType<'_> {
+    pub fn get_name(&self) -> &'static str {
         match self {
-            EventType::None                     => write!(f, "None"),
-            EventType::WindowClose              => write!(f, "WindowClose"),
-            EventType::WindowResize {..}        => write!(f, "WindowResize"),
-            EventType::WindowFocus              => write!(f, "WindowFocus"),
-            EventType::WindowLostFocus          => write!(f, "WindowLostFocus"),
-            EventType::WindowMoved              => write!(f, "WindowMoved"),
-            EventType::AppTick                  => write!(f, "AppTick"),
-            EventType::AppUpdate                => write!(f, "AppUpdate"),
-            EventType::AppRender                => write!(f, "AppRender"),
-            EventType::KeyPressed {..}          => write!(f, "KeyPressed"),
-            EventType::KeyReleased {..}         => write!(f, "KeyReleased"),
-            EventType::MouseButtonPressed {..}  => write!(f, "MouseButtonPressed"),
-            EventType::MouseButtonReleased {..} => write!(f, "MouseButtonReleased"),
-            EventType::MouseMoved {..}          => write!(f, "MouseMoved"),
-            EventType::MouseScrolled {..}       => write!(f, "MouseScrolled"),
-            EventType::WindowRedrawRequest(_)   => write!(f, "WindowRedrawRequest"),
-            EventType::AboutToWait(_)           => write!(f, "AboutToWait"),
-            EventType::NewEvents                => write!(f, "NewEvents")
+            EventType::None                     => "None",
+            EventType::WindowClose              => "WindowClose",
+            EventType::WindowResize {..}        => "WindowResize",
+            EventType::WindowFocus              => "WindowFocus",
+            EventType::WindowLostFocus          => "WindowLostFocus",
+            EventType::WindowMoved              => "WindowMoved",
+            EventType::AppTick                  => "AppTick",
+            EventType::AppUpdate                => "AppUpdate",
+            EventType::AppRender                => "AppRender",
+            EventType::KeyPressed {..}          => "KeyPressed",
+            EventType::KeyReleased {..}         => "KeyReleased",
+            EventType::MouseButtonPressed {..}  => "MouseButtonPressed",
+            EventType::MouseButtonReleased {..} => "MouseButtonReleased",
+            EventType::MouseMoved {..}          => "MouseMoved",
+            EventType::MouseScrolled {..}       => "MouseScrolled",
+            EventType::WindowRedrawRequest(_)   => "WindowRedrawRequest",
+            EventType::AboutToWait(_)           => "AboutToWait",
+            EventType::NewEvents                => "NewEvents",
+            EventType::Winit(..)                => "Winit"
         }
     }
 }
@@ -63,12 +68,12 @@ impl Event<'_> {
         Event {handled: false, event_type}
     }
 
-    pub fn get_event_type(&self) -> EventType {
+    pub fn get_event_type(&self) -> EventType<'_> {
         self.event_type
     }
 
-    pub fn get_name(&self) -> String {
-        self.event_type.to_string()
+    pub fn get_name(&self) -> &'static str {
+        self.event_type.get_name()
     }
 
     pub fn set_handled(&mut self, value: bool) {
@@ -81,18 +86,19 @@ impl Event<'_> {
 
     pub fn get_category_flags(&self) -> i32 {
         match self.event_type {
-            EventType::None => todo!(),
+            EventType::None => EventCategory::None as i32,
             EventType::WindowClose   => EventCategory::EventCategoryApplication as i32,
             EventType::WindowResize {..} => EventCategory::EventCategoryApplication as i32,
-            EventType::WindowFocus => todo!(),
-            EventType::WindowLostFocus => todo!(),
-            EventType::WindowMoved => todo!(),
-            EventType::WindowRedrawRequest(_) => todo!(),
-            EventType::AppTick => todo!(),
-            EventType::AppUpdate => todo!(),
-            EventType::AppRender => todo!(),
-            EventType::AboutToWait(_) => todo!(),
-            EventType::NewEvents => todo!(),
+            EventType::WindowFocus => EventCategory::EventCategoryApplication as i32,
+            EventType::WindowLostFocus => EventCategory::EventCategoryApplication as i32,
+            EventType::WindowMoved => EventCategory::EventCategoryApplication as i32,
+            EventType::WindowRedrawRequest(_) => EventCategory::EventCategoryApplication as i32,
+            EventType::AppTick => EventCategory::EventCategoryApplication as i32,
+            EventType::AppUpdate => EventCategory::EventCategoryApplication as i32,
+            EventType::AppRender => EventCategory::EventCategoryApplication as i32,
+            EventType::AboutToWait(_) => EventCategory::EventCategoryApplication as i32,
+            EventType::NewEvents => EventCategory::EventCategoryApplication as i32,
+            EventType::Winit(..) => EventCategory::EventCategoryInput as i32,
             EventType::KeyPressed {..} => EventCategory::EventCategoryKeyboard as i32 | EventCategory::EventCategoryInput as i32,
             EventType::KeyReleased {..} => EventCategory::EventCategoryKeyboard as i32 | EventCategory::EventCategoryInput as i32,
             EventType::MouseButtonPressed {..} => EventCategory::EventCategoryMouse as i32 | EventCategory::EventCategoryInput as i32,
@@ -105,27 +111,30 @@ impl Event<'_> {
     pub fn is_in_category(&self, category: EventCategory) -> bool {
         self.get_category_flags() & category as i32 > 0
     }
+}
 
-    pub fn to_string(&self) -> String {
+impl Display for Event<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.event_type {
-            EventType::None => todo!(),
-            EventType::WindowClose                                       => format!("WindowClose"),
-            EventType::WindowResize { width, height }          => format!("WindowsResize {}, {}", width, height),
-            EventType::WindowFocus                                       => todo!(),
-            EventType::WindowLostFocus                                   => todo!(),
-            EventType::WindowMoved                                       => todo!(),
-            EventType::WindowRedrawRequest(_)                            => format!("WindowRedrawRequest"),
-            EventType::AppTick                                           => todo!(),
-            EventType::AppUpdate                                         => todo!(),
-            EventType::AppRender                                         => todo!(),
-            EventType::AboutToWait(_)                                    => format!("AboutToWait"),
-            EventType::NewEvents                                         => format!("NewEvents"),
-            EventType::KeyPressed { keycode, repeat_count }    => format!("KeyPressedEvent: {} ({} repeats)", keycode, repeat_count),
-            EventType::KeyReleased { keycode }                      => format!("KeyReleasedEvent {}", keycode),
-            EventType::MouseButtonPressed { button }                => format!("MouseButtonPressed {}", button),
-            EventType::MouseButtonReleased { button }               => format!("MouseButtonReleased {}", button),
-            EventType::MouseMoved { x, y }                     => format!("MouseMovedEvent ({},{})", x, y),
-            EventType::MouseScrolled { x_offset, y_offset }    => format!("MouseScrolledEvent {},{}", x_offset, y_offset),
+            EventType::None                                              => write!(f, "None"),
+            EventType::WindowClose                                       => write!(f, "WindowClose"),
+            EventType::WindowResize { width, height }          => write!(f, "WindowsResize {}, {}", width, height),
+            EventType::WindowFocus                                       => write!(f, "WindowFocus"),
+            EventType::WindowLostFocus                                   => write!(f, "WindowLostFocus"),
+            EventType::WindowMoved                                       => write!(f, "WindowMoved"),
+            EventType::WindowRedrawRequest(_)                            => write!(f, "WindowRedrawRequest"),
+            EventType::AppTick                                           => write!(f, "AppTick"),
+            EventType::AppUpdate                                         => write!(f, "AppUpdate"),
+            EventType::AppRender                                         => write!(f, "AppRender"),
+            EventType::AboutToWait(_)                                    => write!(f, "AboutToWait"),
+            EventType::NewEvents                                         => write!(f, "NewEvents"),
+            EventType::Winit(..)                                         => write!(f, "Winit"),
+            EventType::KeyPressed { keycode, repeat_count }    => write!(f, "KeyPressedEvent: {} ({} repeats)", keycode, repeat_count),
+            EventType::KeyReleased { keycode }                      => write!(f, "KeyReleasedEvent {}", keycode),
+            EventType::MouseButtonPressed { button }                => write!(f, "MouseButtonPressed {}", button),
+            EventType::MouseButtonReleased { button }               => write!(f, "MouseButtonReleased {}", button),
+            EventType::MouseMoved { position }                 => write!(f, "MouseMovedEvent ({},{})", position.x, position.y),
+            EventType::MouseScrolled { offset }                => write!(f, "MouseScrolledEvent {},{}", offset.x, offset.y),
         }
     }
 }
\ No newline at end of file
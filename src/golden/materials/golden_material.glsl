@@ -0,0 +1,52 @@
+#type vertex
+#version 330 core
+
+layout(location = 0) in vec2 a_Position;
+layout(location = 1) in vec2 a_TexCoord;
+
+layout(std140) uniform Camera
+{
+    mat4 u_ViewProjection;
+    vec4 u_CameraPosition;
+};
+
+// The float after the vec3 checks that the offsets follow std140
+layout(std140) uniform Material
+{
+    vec4 u_Color;
+    vec2 u_Offset;
+    float u_Scale;
+    vec3 u_Tint;
+    float u_Tiling;
+};
+
+out vec2 v_TexCoord;
+
+void main()
+{
+    v_TexCoord = a_TexCoord * u_Tiling;
+    gl_Position = u_ViewProjection * vec4(a_Position * u_Scale + u_Offset, 0.0, 1.0);
+}
+
+#type fragment
+#version 330 core
+
+layout(std140) uniform Material
+{
+    vec4 u_Color;
+    vec2 u_Offset;
+    float u_Scale;
+    vec3 u_Tint;
+    float u_Tiling;
+};
+
+in vec2 v_TexCoord;
+
+uniform sampler2D u_Albedo;
+
+out vec4 o_Color;
+
+void main()
+{
+    o_Color = texture(u_Albedo, v_TexCoord) * u_Color * vec4(u_Tint, 1.0);
+}
@@ -0,0 +1,241 @@
+pub mod scenes;
+
+use std::path::{Path, PathBuf};
+
+use image::{Rgba, RgbaImage};
+
+use crate::{
+    application::{Application, HeadlessGraphics, HeadlessSettings},
+    logger::*,
+    renderer::capture
+};
+
+// Largest squared YIQ difference between two colors
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+// Scripted scene that is rendered offscreen and compared with its reference image
+pub struct GoldenTest {
+    pub name: &'static str,
+    pub width: u32,
+    pub height: u32,
+    // Frames rendered before the capture
+    pub frames: u64,
+    pub tolerance: Tolerance,
+    // Pushes the layers that draw the scene, they must only depend on the frame number
+    pub setup: fn(&mut Application) -> Result<(), String>
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tolerance {
+    // Perceptual difference from 0 to 1 above which two pixels are different
+    pub threshold: f32,
+    // Fraction of the pixels that may be different
+    pub max_differing: f32
+}
+
+impl Default for Tolerance {
+    fn default() -> Tolerance {
+        Tolerance { threshold: 0.1, max_differing: 0.001 }
+    }
+}
+
+pub struct Comparison {
+    pub differing_pixels: u64,
+    // Largest perceptual difference of a pixel
+    pub max_difference: f32,
+    // Faded expected image with the different pixels in red
+    pub diff_image: RgbaImage
+}
+
+impl Comparison {
+    pub fn get_differing_fraction(&self) -> f32 {
+        self.differing_pixels as f32 / (self.diff_image.width() * self.diff_image.height()).max(1) as f32
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum GoldenStatus {
+    Passed,
+    // The reference was written from the rendered image
+    Updated,
+    Failed(String)
+}
+
+pub struct GoldenHarness {
+    reference_dir: PathBuf,
+    // Actual and diff images of the failed tests
+    output_dir: PathBuf,
+    update: bool
+}
+
+impl GoldenHarness {
+    pub fn new(reference_dir: impl AsRef<Path>, output_dir: impl AsRef<Path>) -> GoldenHarness {
+        GoldenHarness { reference_dir: reference_dir.as_ref().to_path_buf(), output_dir: output_dir.as_ref().to_path_buf(), update: false }
+    }
+
+    // Writes the rendered images as the new references instead of comparing them
+    pub fn with_update(mut self, update: bool) -> GoldenHarness {
+        self.update = update;
+        self
+    }
+
+    // Runs the tests and returns the number of failures
+    pub fn run_all(&self, tests: &[GoldenTest]) -> usize {
+        let mut failures = 0;
+
+        for test in tests {
+            match self.run(test) {
+                GoldenStatus::Passed => hds_core_info!("Golden test {} passed", test.name),
+                GoldenStatus::Updated => hds_core_info!("Golden test {} updated", test.name),
+                GoldenStatus::Failed(reason) => {
+                    hds_core_error!("Golden test {} failed: {}", test.name, reason);
+                    failures += 1;
+                },
+            }
+        }
+
+        hds_core_info!("{} golden tests, {} failed", tests.len(), failures);
+        failures
+    }
+
+    pub fn run(&self, test: &GoldenTest) -> GoldenStatus {
+        let actual = match render(test) {
+            Ok(image) => image,
+            Err(err) => return GoldenStatus::Failed(format!("Failed to render: {}", err)),
+        };
+
+        let reference_path = self.reference_dir.join(format!("{}.png", test.name));
+        if self.update {
+            return match capture::save_png(&actual, &reference_path) {
+                Ok(()) => GoldenStatus::Updated,
+                Err(err) => GoldenStatus::Failed(err),
+            };
+        }
+
+        let result = image::open(&reference_path)
+            .map_err(|err| format!("Failed to open {}: {}", reference_path.display(), err))
+            .and_then(|expected| compare(&expected.to_rgba8(), &actual, test.tolerance.threshold));
+
+        let (reason, diff_image) = match result {
+            Ok(comparison) if comparison.get_differing_fraction() <= test.tolerance.max_differing => return GoldenStatus::Passed,
+            Ok(comparison) => (
+                format!(
+                    "{} pixels are different ({:.3}%, max difference {:.3})",
+                    comparison.differing_pixels, comparison.get_differing_fraction() * 100.0, comparison.max_difference
+                ),
+                Some(comparison.diff_image)
+            ),
+            Err(err) => (err, None),
+        };
+
+        // The images to look at are written next to each other
+        let actual_path = self.output_dir.join(format!("{}_actual.png", test.name));
+        let diff_path = self.output_dir.join(format!("{}_diff.png", test.name));
+
+        let saved = capture::save_png(&actual, &actual_path)
+            .and_then(|_| diff_image.map_or(Ok(()), |diff_image| capture::save_png(&diff_image, &diff_path)));
+
+        match saved {
+            Ok(()) => GoldenStatus::Failed(format!("{}, see {}", reason, self.output_dir.display())),
+            Err(err) => GoldenStatus::Failed(format!("{} ({})", reason, err)),
+        }
+    }
+}
+
+// Renders the frames of the test on a software GL context, which gives the same pixels on every machine
+pub fn render(test: &GoldenTest) -> Result<RgbaImage, String> {
+    let mut app = Application::new_headless_with_graphics(HeadlessGraphics { width: test.width, height: test.height, software_only: true, ..HeadlessGraphics::default() })?;
+    (test.setup)(&mut app)?;
+
+    app.run_headless(HeadlessSettings { max_frames: Some(test.frames), frame_time: None });
+    app.capture_offscreen_frame().ok_or("The application has no offscreen frames")?
+}
+
+// Counts the pixels with a perceptual difference above the threshold
+pub fn compare(expected: &RgbaImage, actual: &RgbaImage, threshold: f32) -> Result<Comparison, String> {
+    if expected.dimensions() != actual.dimensions() {
+        return Err(format!(
+            "The size is {}x{} instead of {}x{}", actual.width(), actual.height(), expected.width(), expected.height()
+        ));
+    }
+
+    let mut diff_image = RgbaImage::new(expected.width(), expected.height());
+    let mut differing_pixels = 0;
+    let mut max_difference: f32 = 0.0;
+
+    for ((expected_pixel, actual_pixel), diff_pixel) in expected.pixels().zip(actual.pixels()).zip(diff_image.pixels_mut()) {
+        let difference = color_difference(*expected_pixel, *actual_pixel);
+        max_difference = max_difference.max(difference);
+
+        *diff_pixel = if difference > threshold {
+            differing_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        }
+        else {
+            // The rest of the image stays visible for context
+            let gray = (brightness(*expected_pixel) * 0.25 + 191.0) as u8;
+            Rgba([gray, gray, gray, 255])
+        };
+    }
+
+    Ok(Comparison { differing_pixels, max_difference, diff_image })
+}
+
+// Distance in the YIQ color space, which follows how the eye sees the differences,
+// from 0 for equal colors to 1 for the most different ones
+fn color_difference(a: Rgba<u8>, b: Rgba<u8>) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+
+    let (a, b) = (blend_on_white(a), blend_on_white(b));
+    let y = yiq_y(a) - yiq_y(b);
+    let i = yiq_i(a) - yiq_i(b);
+    let q = yiq_q(a) - yiq_q(b);
+
+    ((0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q) / MAX_YIQ_DELTA).sqrt()
+}
+
+// Transparent pixels are compared as they look on a white page
+fn blend_on_white(pixel: Rgba<u8>) -> [f32; 3] {
+    let alpha = pixel[3] as f32 / 255.0;
+    [0, 1, 2].map(|channel| 255.0 + (pixel[channel] as f32 - 255.0) * alpha)
+}
+
+fn brightness(pixel: Rgba<u8>) -> f32 {
+    yiq_y(blend_on_white(pixel))
+}
+
+fn yiq_y([r, g, b]: [f32; 3]) -> f32 {
+    r * 0.298895 + g * 0.586622 + b * 0.114482
+}
+
+fn yiq_i([r, g, b]: [f32; 3]) -> f32 {
+    r * 0.595978 - g * 0.274176 - b * 0.321802
+}
+
+fn yiq_q([r, g, b]: [f32; 3]) -> f32 {
+    r * 0.211470 - g * 0.522617 + b * 0.311147
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::renderer::HeadlessContext;
+
+    use super::*;
+
+    // The references only match on a software GL driver like Mesa llvmpipe, without one there is nothing to compare
+    #[test]
+    fn golden_scenes() {
+        if let Err(err) = HeadlessContext::new(true, false) {
+            eprintln!("Skipping the golden scenes: {}", err);
+            return;
+        }
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let failures = GoldenHarness::new(root.join("golden"), root.join("golden_output")).run_all(&scenes::get_tests());
+        assert_eq!(failures, 0, "Golden scenes failed, see the images in golden_output");
+    }
+}
@@ -0,0 +1,303 @@
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+use imgui::Condition;
+use imgui_glow_renderer::SimpleTextureMap;
+
+use crate::{
+    application::Application,
+    events::{Event, EventType},
+    layers::{imgui_layer, Layer, LayerParam},
+    math::{Mat4, Vec2, Vec3, Vec4},
+    renderer::{
+        BufferElement, BufferLayout, BufferUsage, CameraBuffer, IndexBuffer, Material, OrthographicCamera, RenderCommand, Renderer2D,
+        RendererAPI, Shader, ShaderDataType, Texture2D, TextureSettings, VertexArray, VertexBuffer
+    }
+};
+
+use super::{GoldenTest, Tolerance};
+
+// Small frames keep the reference images light
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 180;
+
+// Scenes checked by the --golden mode
+pub fn get_tests() -> Vec<GoldenTest> {
+    vec![
+        GoldenTest { name: "renderer2d_shapes", width: WIDTH, height: HEIGHT, frames: 3, tolerance: Tolerance::default(), setup: renderer2d_shapes },
+        GoldenTest { name: "renderer2d_textures", width: WIDTH, height: HEIGHT, frames: 3, tolerance: Tolerance::default(), setup: renderer2d_textures },
+        GoldenTest { name: "renderer2d_animation", width: WIDTH, height: HEIGHT, frames: 30, tolerance: Tolerance::default(), setup: renderer2d_animation },
+        GoldenTest { name: "shader_pipeline", width: WIDTH, height: HEIGHT, frames: 3, tolerance: Tolerance::default(), setup: shader_pipeline },
+        GoldenTest { name: "material_blocks", width: WIDTH, height: HEIGHT, frames: 3, tolerance: Tolerance::default(), setup: material_blocks },
+        GoldenTest { name: "imgui_overlay", width: WIDTH, height: HEIGHT, frames: 3, tolerance: Tolerance::default(), setup: imgui_overlay }
+    ]
+}
+
+// Calls the closure with the frame number, from 0, on every AppRender event
+struct SceneLayer {
+    name: String,
+    frame: u64,
+    draw: Box<dyn FnMut(u64)>
+}
+
+impl Layer for SceneLayer {
+    fn on_attach(&mut self, _param: LayerParam) {}
+
+    fn on_detach(&mut self) {}
+
+    fn on_update(&mut self) {}
+
+    fn on_event(&mut self, event: &Event) -> bool {
+        if let EventType::AppRender = event.get_event_type() {
+            (self.draw)(self.frame);
+            self.frame += 1;
+        }
+
+        false
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn push_scene(app: &mut Application, name: &str, draw: impl FnMut(u64) + 'static) {
+    app.push_layer(Box::new(SceneLayer { name: name.to_string(), frame: 0, draw: Box::new(draw) }), LayerParam::None);
+}
+
+fn get_graphics(app: &Application) -> Result<(RenderCommand, Rc<RefCell<Renderer2D>>), String> {
+    match (app.get_render_command(), app.get_renderer_2d()) {
+        (Some(render_command), Some(renderer_2d)) => Ok((render_command, renderer_2d)),
+        _ => Err("The scenes need an application with graphics".to_string()),
+    }
+}
+
+// World of 2 units of height with the origin at the center
+fn camera() -> OrthographicCamera {
+    let aspect_ratio = WIDTH as f32 / HEIGHT as f32;
+    OrthographicCamera::new(-aspect_ratio, aspect_ratio, -1.0, 1.0)
+}
+
+fn renderer2d_shapes(app: &mut Application) -> Result<(), String> {
+    let (render_command, renderer_2d) = get_graphics(app)?;
+    render_command.set_clear_color([0.1, 0.1, 0.12, 1.0]);
+
+    push_scene(app, "renderer2d_shapes", move |_| {
+        let mut renderer = renderer_2d.borrow_mut();
+        renderer.begin_scene(&camera());
+
+        renderer.draw_quad(Vec3::new(-1.1, 0.4, 0.0), Vec2::new(0.5, 0.5), Vec4::new(0.9, 0.2, 0.2, 1.0));
+        renderer.draw_rotated_quad(Vec3::new(-0.3, 0.4, 0.0), Vec2::new(0.5, 0.3), 0.6, Vec4::new(0.2, 0.8, 0.3, 1.0));
+        renderer.draw_circle(Vec3::new(0.5, 0.4, 0.0), 0.3, Vec4::new(0.2, 0.4, 0.9, 1.0), 1.0, 0.01);
+        renderer.draw_circle(Vec3::new(1.2, 0.4, 0.0), 0.3, Vec4::new(0.9, 0.8, 0.2, 1.0), 0.2, 0.05);
+        renderer.draw_rect(Vec3::new(-0.7, -0.5, 0.0), Vec2::new(0.8, 0.5), Vec4::ONE);
+
+        for index in 0..8 {
+            let x = 0.1 + index as f32 * 0.15;
+            renderer.draw_line(Vec3::new(x, -0.8, 0.0), Vec3::new(x + 0.3, -0.2, 0.0), Vec4::new(1.0, 0.5, index as f32 / 8.0, 1.0));
+        }
+
+        renderer.end_scene();
+    });
+
+    Ok(())
+}
+
+fn renderer2d_textures(app: &mut Application) -> Result<(), String> {
+    let (render_command, renderer_2d) = get_graphics(app)?;
+    render_command.set_clear_color([0.15, 0.15, 0.15, 1.0]);
+
+    // 4x4 checkerboard, the nearest filter keeps the squares sharp
+    let pixels: Vec<u8> = (0..16)
+        .flat_map(|index| if (index % 4 + index / 4) % 2 == 0 { [230, 230, 230, 255] } else { [60, 60, 160, 255] })
+        .collect();
+    let checkerboard = Rc::new(Texture2D::from_rgba(&render_command, 4, 4, &pixels, TextureSettings::nearest())?);
+
+    push_scene(app, "renderer2d_textures", move |_| {
+        let mut renderer = renderer_2d.borrow_mut();
+        renderer.begin_scene(&camera());
+
+        renderer.draw_textured_quad(Vec3::new(-1.0, 0.0, 0.0), Vec2::new(1.0, 1.0), &checkerboard, 1.0, Vec4::ONE);
+        renderer.draw_textured_quad(Vec3::new(0.2, 0.0, 0.0), Vec2::new(1.0, 1.0), &checkerboard, 3.0, Vec4::new(1.0, 0.6, 0.6, 1.0));
+        renderer.draw_rotated_textured_quad(Vec3::new(1.3, 0.0, 0.0), Vec2::new(0.6, 0.6), 0.4, &checkerboard, 1.0, Vec4::ONE);
+
+        // Transparent quad over the others to check the blending
+        renderer.draw_quad(Vec3::new(-0.4, -0.4, 0.1), Vec2::new(1.6, 0.4), Vec4::new(0.1, 0.9, 0.4, 0.5));
+
+        renderer.end_scene();
+    });
+
+    Ok(())
+}
+
+// The state after several frames, the positions only depend on the frame number
+fn renderer2d_animation(app: &mut Application) -> Result<(), String> {
+    let (render_command, renderer_2d) = get_graphics(app)?;
+    render_command.set_clear_color([0.05, 0.05, 0.1, 1.0]);
+
+    push_scene(app, "renderer2d_animation", move |frame| {
+        let time = frame as f32 / 30.0;
+        let mut renderer = renderer_2d.borrow_mut();
+        renderer.begin_scene(&camera());
+
+        for index in 0..6 {
+            let angle = time * 2.0 + index as f32 * std::f32::consts::TAU / 6.0;
+            let position = Vec3::new(angle.cos() * 0.6, angle.sin() * 0.6, 0.0);
+            renderer.draw_rotated_quad(position, Vec2::splat(0.2), angle, Vec4::new(0.3 + index as f32 * 0.12, 0.5, 0.9, 1.0));
+        }
+
+        renderer.draw_circle(Vec3::new(-1.3 + time * 2.0, -0.7, 0.0), 0.15, Vec4::new(1.0, 0.4, 0.2, 1.0), 1.0, 0.02);
+        renderer.end_scene();
+    });
+
+    Ok(())
+}
+
+// Shader, buffer layout and vertex array without the 2D renderer
+fn shader_pipeline(app: &mut Application) -> Result<(), String> {
+    let (render_command, _) = get_graphics(app)?;
+    render_command.set_clear_color([0.0, 0.0, 0.0, 1.0]);
+
+    let shader = Shader::from_source(&render_command, "golden_pipeline", include_str!("shaders/golden_pipeline.glsl"))?;
+
+    // Position and color of the corners of a square
+    let vertices: [f32; 24] = [
+        -0.5, -0.5, 1.0, 0.0, 0.0, 1.0,
+         0.5, -0.5, 0.0, 1.0, 0.0, 1.0,
+         0.5,  0.5, 0.0, 0.0, 1.0, 1.0,
+        -0.5,  0.5, 1.0, 1.0, 1.0, 1.0
+    ];
+
+    let mut vertex_buffer = VertexBuffer::new(&render_command, &vertices, BufferUsage::Static);
+    vertex_buffer.set_layout(BufferLayout::new(vec![
+        BufferElement::new(ShaderDataType::Float2, "a_Position"),
+        BufferElement::new(ShaderDataType::Float4, "a_Color")
+    ]));
+
+    let mut vertex_array = VertexArray::new(&render_command);
+    vertex_array.add_vertex_buffer(vertex_buffer);
+    vertex_array.set_index_buffer(IndexBuffer::new(&render_command, &[0, 1, 2, 2, 3, 0], BufferUsage::Static));
+
+    push_scene(app, "shader_pipeline", move |_| {
+        let view_projection = *camera().get_projection();
+
+        shader.bind();
+        shader.set_float("u_Brightness", 0.8);
+
+        for (index, offset) in [-1.0, 0.0, 1.0].into_iter().enumerate() {
+            let transform = Mat4::from_translation(Vec3::new(offset, 0.0, 0.0)) * Mat4::from_rotation_z(index as f32 * 0.3);
+            shader.set_mat4("u_Transform", &(view_projection * transform));
+            render_command.draw_indexed(&vertex_array, None);
+        }
+    });
+
+    Ok(())
+}
+
+// Material loaded from its file, drawn by instances that override some of its values
+fn material_blocks(app: &mut Application) -> Result<(), String> {
+    let (render_command, _) = get_graphics(app)?;
+    render_command.set_clear_color([0.1, 0.1, 0.1, 1.0]);
+
+    let (shader_library, texture_library) = match (app.get_shader_library(), app.get_texture_library()) {
+        (Some(shader_library), Some(texture_library)) => (shader_library, texture_library),
+        _ => return Err("The scenes need an application with graphics".to_string()),
+    };
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/golden/materials/golden_material.material");
+    let material = Rc::new(Material::from_file(&render_command, path, &mut shader_library.borrow_mut(), &mut texture_library.borrow_mut())?);
+
+    // Position and texture coordinates of the corners of a square
+    let vertices: [f32; 16] = [
+        -0.5, -0.5, 0.0, 0.0,
+         0.5, -0.5, 1.0, 0.0,
+         0.5,  0.5, 1.0, 1.0,
+        -0.5,  0.5, 0.0, 1.0
+    ];
+
+    let mut vertex_buffer = VertexBuffer::new(&render_command, &vertices, BufferUsage::Static);
+    vertex_buffer.set_layout(BufferLayout::new(vec![
+        BufferElement::new(ShaderDataType::Float2, "a_Position"),
+        BufferElement::new(ShaderDataType::Float2, "a_TexCoord")
+    ]));
+
+    let mut vertex_array = VertexArray::new(&render_command);
+    vertex_array.add_vertex_buffer(vertex_buffer);
+    vertex_array.set_index_buffer(IndexBuffer::new(&render_command, &[0, 1, 2, 2, 3, 0], BufferUsage::Static));
+
+    let mut left = material.create_instance();
+    left.set_float2("u_Offset", Vec2::new(-0.9, 0.2));
+
+    let mut right = material.create_instance();
+    right.set_float2("u_Offset", Vec2::new(0.9, 0.2));
+    right.set_color("u_Color", Vec4::new(0.9, 0.4, 0.3, 1.0));
+    right.set_float("u_Tiling", 4.0);
+
+    // Transparent, over the other two
+    let mut overlay = material.create_instance();
+    overlay.set_float2("u_Offset", Vec2::new(0.0, -0.3));
+    overlay.set_float("u_Scale", 1.2);
+    overlay.set_color("u_Color", Vec4::new(0.3, 1.0, 0.5, 0.5));
+
+    let camera_buffer = CameraBuffer::new(&render_command);
+
+    push_scene(app, "material_blocks", move |_| {
+        camera_buffer.set_camera(&camera());
+
+        for instance in [&left, &right, &overlay] {
+            instance.bind();
+            render_command.draw_indexed(&vertex_array, None);
+        }
+    });
+
+    Ok(())
+}
+
+// Imgui windows over a 2D scene, drawn with the context and the renderer of the imgui layer
+fn imgui_overlay(app: &mut Application) -> Result<(), String> {
+    let (render_command, renderer_2d) = get_graphics(app)?;
+    render_command.set_clear_color([0.1, 0.1, 0.12, 1.0]);
+
+    // The layer takes the size and the time step from the window, the scene fixes them
+    let mut imgui = imgui_layer::create_context();
+    imgui.io_mut().display_size = [WIDTH as f32, HEIGHT as f32];
+    imgui.io_mut().delta_time = 1.0 / 60.0;
+
+    let mut texture_map = SimpleTextureMap::default();
+    let mut renderer = imgui_layer::create_renderer(&render_command, &mut imgui, &mut texture_map)?;
+
+    // Engine texture shown through its imgui id
+    let pixels: Vec<u8> = (0..64).flat_map(|index| [(index % 8) as u8 * 32, (index / 8) as u8 * 32, 200, 255]).collect();
+    let gradient = Texture2D::from_rgba(&render_command, 8, 8, &pixels, TextureSettings::nearest())?;
+
+    push_scene(app, "imgui_overlay", move |_| {
+        {
+            let mut renderer = renderer_2d.borrow_mut();
+            renderer.begin_scene(&camera());
+            renderer.draw_quad(Vec3::new(-0.6, 0.0, 0.0), Vec2::new(1.2, 1.2), Vec4::new(0.9, 0.4, 0.2, 1.0));
+            renderer.draw_circle(Vec3::new(0.9, -0.3, 0.0), 0.5, Vec4::new(0.2, 0.6, 0.9, 1.0), 1.0, 0.01);
+            renderer.end_scene();
+        }
+
+        let ui = imgui.new_frame();
+
+        ui.window("Golden")
+            .position([10.0, 10.0], Condition::Always)
+            .size([170.0, 125.0], Condition::Always)
+            .build(|| {
+                ui.text("Hades");
+                ui.button("Button");
+                ui.checkbox("Checkbox", &mut true);
+                imgui::ProgressBar::new(0.6).build(ui);
+            });
+
+        ui.window("Texture")
+            .position([200.0, 40.0], Condition::Always)
+            .size([100.0, 100.0], Condition::Always)
+            .build(|| imgui::Image::new(gradient.get_imgui_id(), [64.0, 64.0]).build(ui));
+
+        let draw_data = imgui.render();
+        imgui_layer::render_draw_data(&mut renderer, &texture_map, &render_command, draw_data);
+    });
+
+    Ok(())
+}
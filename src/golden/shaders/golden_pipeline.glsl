@@ -0,0 +1,29 @@
+#type vertex
+#version 330 core
+
+layout(location = 0) in vec2 a_Position;
+layout(location = 1) in vec4 a_Color;
+
+uniform mat4 u_Transform;
+
+out vec4 v_Color;
+
+void main()
+{
+    v_Color = a_Color;
+    gl_Position = u_Transform * vec4(a_Position, 0.0, 1.0);
+}
+
+#type fragment
+#version 330 core
+
+in vec4 v_Color;
+
+uniform float u_Brightness;
+
+out vec4 o_Color;
+
+void main()
+{
+    o_Color = vec4(v_Color.rgb * u_Brightness, v_Color.a);
+}
@@ -1,54 +1,218 @@
-use glow::HasContext;
-use glutin::{
-    context::PossiblyCurrentContext,
-    display::{GetGlDisplay, GlDisplay}};
 use imgui::{
-    Context, Style, BackendFlags
+    Context, Style, BackendFlags, DrawData, Ui
 };
+use log::LevelFilter;
 
-use imgui_glow_renderer::AutoRenderer;
+use imgui_glow_renderer::{Renderer, SimpleTextureMap};
 use imgui_winit_support::WinitPlatform;
-use winit::window::Window;
-use std::time::Instant;
+use winit::{keyboard::KeyCode, window::Window};
+use std::{cell::RefCell, rc::{Rc, Weak}, time::{Duration, Instant}};
 
 use crate::{
-    events::{Event, EventType}, layers::{Layer, LayerParam}
+    console::Console, events::{Event, EventType}, layers::{log_console::LogConsole, Layer, LayerParam}, logger::{self, *},
+    renderer::{Framebuffer, RenderCommand, Renderer2D, ShaderLibrary, TextureLibrary}
 };
 
+const LEVEL_NAMES: [&str; 6] = ["OFF", "ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
+const LEVEL_FILTERS: [LevelFilter; 6] = [
+    LevelFilter::Off, LevelFilter::Error, LevelFilter::Warn, LevelFilter::Info, LevelFilter::Debug, LevelFilter::Trace
+];
+
+// Framebuffers shown in imgui windows, with the window title and the color attachment
+pub type ImguiViewports = Rc<RefCell<Vec<(String, Weak<RefCell<Framebuffer>>, usize)>>>;
+
+// Context with the style and the fonts of the engine, the window is attached by the layer
+pub(crate) fn create_context() -> Context {
+    let mut imgui = Context::create();
+    imgui.set_ini_filename(None);
+    Style::use_dark_colors(imgui.style_mut());
+
+    imgui
+        .fonts()
+        .add_font(&[imgui::FontSource::DefaultFontData { config: None }]);
+
+    imgui
+}
+
+// Imgui draws with the GL context shared by the whole application
+pub(crate) fn create_renderer(render_command: &RenderCommand, imgui: &mut Context, texture_map: &mut SimpleTextureMap) -> Result<Renderer, String> {
+    Renderer::initialize(render_command.gl(), imgui, texture_map, true)
+        .map_err(|err| format!("Failed to create the imgui renderer: {}", err))
+}
+
+pub(crate) fn render_draw_data(renderer: &mut Renderer, texture_map: &SimpleTextureMap, render_command: &RenderCommand, draw_data: &DrawData) {
+    // Imgui draws with its own GL calls, they are counted from its draw lists
+    for draw_list in draw_data.draw_lists() {
+        for command in draw_list.commands() {
+            if let imgui::DrawCmd::Elements { count, .. } = command {
+                render_command.get_stats_counter().add_draw_call(count as u64);
+            }
+        }
+    }
+
+    if let Err(err) = renderer.render(render_command.gl(), texture_map, draw_data) {
+        hds_core_error!("Failed to render imgui: {}", err);
+    }
+
+    // Outside of the renderer, so its errors are checked here
+    render_command.check_errors("imgui render");
+}
+
+// The backquote opens and closes the log console, except while it is typed in a text field like the command line
+fn is_console_toggle(event_type: EventType, want_text_input: bool) -> bool {
+    let backquote = matches!(event_type, EventType::KeyPressed { keycode, repeat_count: 0 } if keycode == KeyCode::Backquote as i32);
+    backquote && !want_text_input
+}
+
 pub struct ImguiLayer{
     imgui: Option<Context>,
     platform: Option<WinitPlatform>,
-    renderer: Option<AutoRenderer>,
-    last_frame: Instant
+    renderer: Option<Renderer>,
+    // Imgui texture ids are the GL texture names, so any texture of the engine can be shown
+    texture_map: SimpleTextureMap,
+    render_command: RenderCommand,
+    log_console: LogConsole,
+    shader_library: Rc<RefCell<ShaderLibrary>>,
+    texture_library: Rc<RefCell<TextureLibrary>>,
+    viewports: ImguiViewports,
+    renderer_2d: Rc<RefCell<Renderer2D>>,
+    last_frame: Instant,
+    // CPU time between the last two frames
+    frame_time: Duration
 }
 
 impl ImguiLayer {
-    pub fn new(window: &Window, context: &PossiblyCurrentContext) -> ImguiLayer {
-        let mut imgui = Context::create();
-        imgui.set_ini_filename(None);
-        Style::use_dark_colors(imgui.style_mut());
+    pub fn new(window: &Window, render_command: RenderCommand, console: Rc<RefCell<Console>>,
+        shader_library: Rc<RefCell<ShaderLibrary>>, texture_library: Rc<RefCell<TextureLibrary>>,
+        viewports: ImguiViewports, renderer_2d: Rc<RefCell<Renderer2D>>) -> ImguiLayer {
+        let mut imgui = create_context();
 
         imgui.io_mut().backend_flags |= BackendFlags::HAS_MOUSE_CURSORS;
         imgui.io_mut().backend_flags |= BackendFlags::HAS_SET_MOUSE_POS;
 
         let mut winit_platform = WinitPlatform::init(&mut imgui);
         winit_platform.attach_window(imgui.io_mut(), window, imgui_winit_support::HiDpiMode::Rounded);
-        
-        imgui
-            .fonts()
-            .add_font(&[imgui::FontSource::DefaultFontData { config: None }]);
 
         imgui.io_mut().font_global_scale = (1.0 / winit_platform.hidpi_factor()) as f32;
 
-        // OpenGL glow context
-        let gl = unsafe {
-            glow::Context::from_loader_function_cstr(|s| context.display().get_proc_address(s).cast())
-        };
+        let mut texture_map = SimpleTextureMap::default();
+        let renderer = create_renderer(&render_command, &mut imgui, &mut texture_map).unwrap_or_else(|err| panic!("{}", err));
+
+        ImguiLayer {
+            imgui: Some(imgui), platform: Some(winit_platform), renderer: Some(renderer), texture_map, render_command,
+            log_console: LogConsole::new(console), shader_library, texture_library, viewports, renderer_2d,
+            last_frame: Instant::now(), frame_time: Duration::ZERO
+        }
+    }
+
+    // Window to change the level of the log targets while the application runs
+    fn show_log_levels(ui: &Ui) {
+        ui.window("Log Levels").build(|| {
+            let filters = logger::get_filters();
+
+            let mut index = LEVEL_FILTERS.iter().position(|level| *level == filters.get_default()).unwrap_or(0);
+            if ui.combo_simple_string("Default", &mut index, &LEVEL_NAMES) {
+                logger::set_default_level(LEVEL_FILTERS[index]);
+            }
+
+            // The engine and application targets are always listed, even without their own filter
+            let mut targets = vec!["HADES", "APP"];
+            targets.extend(filters.get_targets().iter().map(|(target, _)| target.as_str()).filter(|target| !["HADES", "APP"].contains(target)));
+
+            for target in targets {
+                let level = filters.get_level(target);
+                let mut index = LEVEL_FILTERS.iter().position(|filter| *filter == level).unwrap_or(0);
+                if ui.combo_simple_string(target, &mut index, &LEVEL_NAMES) {
+                    logger::set_target_level(target, LEVEL_FILTERS[index]);
+                }
+            }
+        });
+    }
+
+    // Overlay with the shaders that failed to reload, they keep drawing with their last good program
+    fn show_shader_errors(ui: &Ui, shader_library: &ShaderLibrary) {
+        let errors = shader_library.get_errors();
+        if errors.is_empty() {
+            return;
+        }
+
+        ui.window("Shader Errors")
+            .position([10.0, 10.0], imgui::Condition::FirstUseEver)
+            .bg_alpha(0.85)
+            .always_auto_resize(true)
+            .build(|| {
+                for (name, error) in errors {
+                    ui.text_colored([1.0, 0.4, 0.4, 1.0], name);
+                    ui.text_wrapped(error);
+                    ui.separator();
+                }
+            });
+    }
 
-        let renderer = imgui_glow_renderer::AutoRenderer::initialize(gl, &mut imgui)
-            .expect("Failed to create OpenGL renderer");
+    // Batches of the 2D renderer in the previous frame
+    fn show_renderer_2d_stats(ui: &Ui, renderer_2d: &Renderer2D) {
+        let stats = renderer_2d.get_stats();
 
-        ImguiLayer { imgui: Some(imgui), platform: Some(winit_platform), renderer: Some(renderer), last_frame: Instant::now() } 
+        ui.window("Renderer2D Stats").always_auto_resize(true).build(|| {
+            ui.text(format!("Draw calls: {}", stats.draw_calls));
+            ui.text(format!("Quads: {}", stats.quad_count));
+            ui.text(format!("Circles: {}", stats.circle_count));
+            ui.text(format!("Lines: {}", stats.line_count));
+            ui.text(format!("Vertices: {}", stats.get_vertex_count()));
+            ui.text(format!("Indices: {}", stats.get_index_count()));
+        });
+    }
+
+    // Overlay in the top right corner with the frame times and the GL work of the previous frame
+    fn show_render_stats(ui: &Ui, render_command: &RenderCommand, frame_time: Duration) {
+        let stats = render_command.get_stats();
+        let gpu_times = render_command.get_gpu_times();
+        let display_size = ui.io().display_size;
+
+        ui.window("Render Stats")
+            .position([display_size[0] - 10.0, 10.0], imgui::Condition::Always)
+            .position_pivot([1.0, 0.0])
+            .bg_alpha(0.6)
+            .no_decoration()
+            .always_auto_resize(true)
+            .build(|| {
+                let milliseconds = frame_time.as_secs_f32() * 1000.0;
+                let fps = if milliseconds > 0.0 { 1000.0 / milliseconds } else { 0.0 };
+                ui.text(format!("CPU frame: {:.2} ms ({:.0} FPS)", milliseconds, fps));
+
+                // The GPU results arrive some frames late
+                for (pass, time) in &gpu_times {
+                    ui.text(format!("GPU {}: {:.2} ms", pass, time));
+                }
+
+                ui.separator();
+                ui.text(format!("Draw calls: {}", stats.draw_calls));
+                ui.text(format!("Vertices: {}", stats.vertices));
+                ui.text(format!("State changes: {}", stats.state_changes));
+                ui.text(format!("Texture binds: {}", stats.texture_binds));
+                ui.text(format!("Uploads: {} buffers, {} textures ({:.1} KB)",
+                    stats.buffer_uploads, stats.texture_uploads, stats.uploaded_bytes as f32 / 1024.0));
+            });
+    }
+
+    // Loaded textures drawn with their imgui ids, which are the GL names
+    fn show_textures(ui: &Ui, texture_library: &TextureLibrary) {
+        let mut textures: Vec<_> = texture_library.get_textures().collect();
+        if textures.is_empty() {
+            return;
+        }
+        textures.sort_by_key(|texture| texture.get_path().map(|path| path.to_path_buf()));
+
+        ui.window("Textures").build(|| {
+            for texture in textures {
+                let path = texture.get_path().map_or("".to_string(), |path| path.display().to_string());
+                ui.text(format!("{} ({}x{})", path, texture.get_width(), texture.get_height()));
+
+                // Thumbnails keep the aspect ratio of the texture
+                let scale = 128.0 / texture.get_width().max(texture.get_height()).max(1) as f32;
+                imgui::Image::new(texture.get_imgui_id(), [texture.get_width() as f32 * scale, texture.get_height() as f32 * scale]).build(ui);
+            }
+        });
     }
 }
 
@@ -81,9 +245,8 @@ impl Layer for ImguiLayer {
         self.last_frame = Instant::now();
     }
 
-    fn on_detach(&mut self) {
-        todo!()
-    }
+    // The GL objects of imgui are freed when the layer is dropped
+    fn on_detach(&mut self) {}
 
     fn on_update(&mut self) {
         let now = Instant::now();
@@ -93,13 +256,32 @@ impl Layer for ImguiLayer {
 
     fn on_event(&mut self, event: &Event) -> bool {
         match event.get_event_type() {
+            event_type if is_console_toggle(event_type, self.imgui.as_ref().is_some_and(|imgui| imgui.io().want_text_input)) => {
+                self.log_console.set_open(!self.log_console.is_open());
+                return true;
+            },
+            EventType::Winit(winit_event, window) => {
+                if let (Some(imgui), Some(platform)) = (&mut self.imgui, &mut self.platform) {
+                    platform.handle_event(imgui.io_mut(), window, winit_event);
+                }
+            },
+            // The input used by imgui doesn't reach the game layers, the releases always do
+            // so the layers don't keep keys or buttons pressed
+            EventType::KeyPressed { .. } => {
+                return self.imgui.as_ref().is_some_and(|imgui| imgui.io().want_capture_keyboard);
+            },
+            EventType::MouseButtonPressed { .. } | EventType::MouseMoved { .. } | EventType::MouseScrolled { .. } => {
+                return self.imgui.as_ref().is_some_and(|imgui| imgui.io().want_capture_mouse);
+            },
             EventType::NewEvents => {
                 let now = Instant::now();
 
+                self.frame_time = now.duration_since(self.last_frame);
+
                 if let Some(imgui) = &mut self.imgui {                    
                     imgui
                         .io_mut()
-                        .update_delta_time(now.duration_since(self.last_frame))
+                        .update_delta_time(self.frame_time)
                 };
 
                 self.last_frame = now;
@@ -112,19 +294,28 @@ impl Layer for ImguiLayer {
                 }
             },
             EventType::WindowRedrawRequest(window) => {
-                unsafe { 
-                    if let Some(renderer) = &self.renderer {
-                        renderer.gl_context().clear(glow::COLOR_BUFFER_BIT)
-                    }
-                };
-
                 let ui =  match &mut self.imgui {
                     Some(imgui) => imgui.frame(),
-                    None => todo!(),
+                    None => return false,
                 };
 
                 // For now only show the demo window in imgui
                 ui.show_demo_window(&mut true);
+                Self::show_log_levels(ui);
+                self.log_console.draw(ui);
+                Self::show_shader_errors(ui, &self.shader_library.borrow());
+                Self::show_textures(ui, &self.texture_library.borrow());
+                Self::show_renderer_2d_stats(ui, &self.renderer_2d.borrow());
+                Self::show_render_stats(ui, &self.render_command, self.frame_time);
+
+                // Framebuffers that were dropped leave their viewport
+                self.viewports.borrow_mut().retain(|(title, framebuffer, index)| match framebuffer.upgrade() {
+                    Some(framebuffer) => {
+                        framebuffer.borrow_mut().show_viewport(ui, title, *index);
+                        true
+                    },
+                    None => false,
+                });
 
                 if let Some(platform) = &mut self.platform {
                     platform.prepare_render(ui, window)
@@ -132,12 +323,11 @@ impl Layer for ImguiLayer {
 
                 let draw_data =  match &mut self.imgui {
                     Some(imgui) => imgui.render(),
-                    None => todo!(),
+                    None => return false,
                 };
 
                 if let Some(renderer) = &mut self.renderer {
-                    renderer.render(draw_data)
-                        .expect("Error rendering imgui");
+                    render_draw_data(renderer, &self.texture_map, &self.render_command, draw_data);
                 }
             },
             _ => ()
@@ -149,4 +339,27 @@ impl Layer for ImguiLayer {
     fn get_name(&self) -> &str {
         "ImguiLayer"
     }
-}
\ No newline at end of file
+}
+
+impl Drop for ImguiLayer {
+    fn drop(&mut self) {
+        if let Some(renderer) = &mut self.renderer {
+            renderer.destroy(self.render_command.gl());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backquote_toggles_the_console_outside_text_fields() {
+        let backquote = EventType::KeyPressed { keycode: KeyCode::Backquote as i32, repeat_count: 0 };
+
+        assert!(is_console_toggle(backquote, false));
+        assert!(!is_console_toggle(backquote, true));
+        assert!(!is_console_toggle(EventType::KeyPressed { keycode: KeyCode::Backquote as i32, repeat_count: 1 }, false));
+        assert!(!is_console_toggle(EventType::KeyPressed { keycode: KeyCode::KeyA as i32, repeat_count: 0 }, false));
+    }
+}
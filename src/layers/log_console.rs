@@ -0,0 +1,272 @@
+use std::{cell::RefCell, rc::Rc};
+
+use imgui::{HistoryDirection, InputTextCallback, InputTextCallbackHandler, TextCallbackData, Ui};
+use log::Level;
+
+use crate::{console::Console, logger::{self, LogRecord}};
+
+const LEVELS: [Level; 5] = [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+// Same colors the terminal output uses for each level
+fn level_color(level: Level) -> [f32; 4] {
+    match level {
+        Level::Error => [0.94, 0.33, 0.31, 1.0],
+        Level::Warn  => [0.98, 0.82, 0.30, 1.0],
+        Level::Info  => [0.90, 0.90, 0.90, 1.0],
+        Level::Debug => [0.40, 0.60, 1.00, 1.0],
+        Level::Trace => [0.30, 0.85, 0.90, 1.0]
+    }
+}
+
+// Tab completion and history navigation of the command line
+struct CommandLineHandler<'a> {
+    console: &'a Console,
+    history_position: &'a mut Option<usize>
+}
+
+impl InputTextCallbackHandler for CommandLineHandler<'_> {
+    fn on_completion(&mut self, mut data: TextCallbackData) {
+        let text = data.str().to_string();
+        let candidates = self.console.complete(text.trim());
+
+        match candidates.as_slice() {
+            [] => (),
+            [name] => {
+                data.clear();
+                data.push_str(name);
+                data.push_str(" ");
+            },
+            names => {
+                // Complete the common part and list the options
+                let common = names.iter().skip(1).fold(names[0], |common, name| {
+                    // Byte offset of the first different char, the names are not always ASCII
+                    let length = common.char_indices().zip(name.chars())
+                        .find(|((_, a), b)| a != b)
+                        .map_or(common.len().min(name.len()), |((index, _), _)| index);
+                    &common[..length]
+                });
+
+                data.clear();
+                data.push_str(common);
+                crate::console::print(&names.join("  "));
+            },
+        }
+    }
+
+    fn on_history(&mut self, direction: HistoryDirection, mut data: TextCallbackData) {
+        let history = self.console.get_history();
+        if history.is_empty() {
+            return;
+        }
+
+        *self.history_position = match (direction, *self.history_position) {
+            (HistoryDirection::Up, None) => Some(history.len() - 1),
+            (HistoryDirection::Up, Some(position)) => Some(position.saturating_sub(1)),
+            (HistoryDirection::Down, Some(position)) if position + 1 < history.len() => Some(position + 1),
+            (HistoryDirection::Down, _) => None,
+        };
+
+        data.clear();
+        if let Some(position) = *self.history_position {
+            data.push_str(&history[position]);
+        }
+    }
+}
+
+// Record that passed the filters, formatted once when it arrives
+struct VisibleLine {
+    // Position of the record in the history total
+    sequence: u64,
+    level: Level,
+    text: String
+}
+
+// Filters the visible lines were built with
+#[derive(Clone, PartialEq, Default)]
+struct Filters {
+    levels: [bool; 5],
+    targets: String,
+    search: String
+}
+
+// Imgui window that shows the records kept in the logger history and runs console commands
+pub struct LogConsole {
+    console: Rc<RefCell<Console>>,
+    command: String,
+    history_position: Option<usize>,
+    open: bool,
+    levels: [bool; 5],
+    // Comma separated list of targets to show, empty shows everything
+    targets: String,
+    search: String,
+    auto_scroll: bool,
+    lines: Vec<VisibleLine>,
+    lines_filters: Filters,
+    // History total the lines are up to date with
+    lines_total: u64
+}
+
+impl LogConsole {
+    pub fn new(console: Rc<RefCell<Console>>) -> LogConsole {
+        LogConsole { console, command: String::new(), history_position: None, open: true, levels: [true; 5], targets: String::new(), search: String::new(), auto_scroll: true, lines: Vec::new(), lines_filters: Filters::default(), lines_total: 0 }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open
+    }
+
+    // The search is already lowercase
+    fn is_visible(&self, record: &LogRecord, search: &str) -> bool {
+        let level_index = LEVELS.iter().position(|level| *level == record.get_level()).unwrap_or(0);
+        if !self.levels[level_index] {
+            return false;
+        }
+
+        let targets = self.targets.trim();
+        if !targets.is_empty() && !targets.split(',').any(|target| record.get_target().starts_with(target.trim())) {
+            return false;
+        }
+
+        search.is_empty() || record.get_message().to_lowercase().contains(search)
+    }
+
+    // Formats the records added since the last frame, or all of them when the filters changed
+    fn update_lines(&mut self) {
+        let filters = Filters { levels: self.levels, targets: self.targets.clone(), search: self.search.clone() };
+        let mut seen = self.lines_total;
+        if filters != self.lines_filters {
+            self.lines_filters = filters;
+            self.lines.clear();
+            seen = 0;
+        }
+
+        // Only copy under the lock, it blocks every thread that logs
+        let copied = logger::with_history(|history| {
+            let total = history.get_total();
+            if total == seen {
+                return None;
+            }
+
+            let first = total - history.get_records().len() as u64;
+            // A smaller total means the history was cleared
+            let start = if total < seen { first } else { seen.max(first) };
+            let records: Vec<LogRecord> = history.get_records().iter().skip((start - first) as usize).cloned().collect();
+            Some((total, first, start, total < seen, records))
+        }).flatten();
+
+        let Some((total, first, start, cleared, records)) = copied else {
+            return;
+        };
+
+        if cleared {
+            self.lines.clear();
+        }
+
+        // Drop the lines of the records the history no longer keeps
+        let dropped = self.lines.partition_point(|line| line.sequence < first);
+        self.lines.drain(..dropped);
+
+        let search = self.search.to_lowercase();
+        for (sequence, record) in (start..).zip(records) {
+            if self.is_visible(&record, &search) {
+                self.lines.push(VisibleLine { sequence, level: record.get_level(), text: record.format(false) });
+            }
+        }
+
+        self.lines_total = total;
+    }
+
+    pub fn draw(&mut self, ui: &Ui) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        ui.window("Log Console")
+            .size([720.0, 320.0], imgui::Condition::FirstUseEver)
+            .opened(&mut open)
+            .build(|| self.draw_contents(ui));
+        self.open = open;
+    }
+
+    fn draw_contents(&mut self, ui: &Ui) {
+        for (index, level) in LEVELS.iter().enumerate() {
+            let _color = ui.push_style_color(imgui::StyleColor::Text, level_color(*level));
+            ui.checkbox(level.as_str(), &mut self.levels[index]);
+            ui.same_line();
+        }
+
+        ui.checkbox("Auto-scroll", &mut self.auto_scroll);
+        ui.same_line();
+
+        let clear = ui.button("Clear");
+        ui.same_line();
+        let copy = ui.button("Copy");
+
+        ui.set_next_item_width(200.0);
+        ui.input_text("Targets", &mut self.targets).hint("HADES, APP").build();
+        ui.same_line();
+        ui.set_next_item_width(-1.0);
+        ui.input_text("##search", &mut self.search).hint("Search").build();
+
+        ui.separator();
+
+        if clear {
+            logger::clear_history();
+            self.lines.clear();
+            self.lines_total = 0;
+        }
+
+        self.update_lines();
+
+        if copy {
+            let copied: String = self.lines.iter().map(|line| format!("{}\n", line.text)).collect();
+            ui.set_clipboard_text(copied);
+        }
+
+        // Leave room for the command line under the records
+        let footer_height = ui.frame_height_with_spacing();
+
+        ui.child_window("LogRecords").size([0.0, -footer_height]).horizontal_scrollbar(true).build(|| {
+            for line in &self.lines {
+                ui.text_colored(level_color(line.level), &line.text);
+            }
+
+            // Follow the new records only when the view was already at the bottom
+            if self.auto_scroll && ui.scroll_y() >= ui.scroll_max_y() {
+                ui.set_scroll_here_y_with_ratio(1.0);
+            }
+        });
+
+        self.draw_command_line(ui);
+    }
+
+    fn draw_command_line(&mut self, ui: &Ui) {
+        ui.separator();
+        ui.set_next_item_width(-1.0);
+
+        let submitted = {
+            let console = self.console.borrow();
+            let handler = CommandLineHandler { console: &console, history_position: &mut self.history_position };
+
+            ui.input_text("##command", &mut self.command)
+                .hint("Command, Tab to complete")
+                .enter_returns_true(true)
+                .callback(InputTextCallback::COMPLETION | InputTextCallback::HISTORY, handler)
+                .build()
+        };
+
+        if submitted {
+            self.console.borrow_mut().submit(&self.command);
+            self.command.clear();
+            self.history_position = None;
+
+            // Keep typing commands after pressing enter
+            ui.set_keyboard_focus_here_with_offset(imgui::FocusedWidget::Previous);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod imgui_layer;
+pub mod log_console;
 
 use imgui_winit_support::winit::window::Window;
 
@@ -20,18 +21,53 @@ pub trait Layer {
 
 pub struct LayerStack {
     layers: Vec<Box<dyn Layer>>,
-    layer_insert: usize
+    layer_insert: usize,
+    // Names of the layers that don't receive updates or events
+    disabled: Vec<String>
+}
+
+impl Default for LayerStack {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl LayerStack {
     pub fn new() -> LayerStack {
-        LayerStack { layers: vec![], layer_insert: 0 }
+        LayerStack { layers: vec![], layer_insert: 0, disabled: vec![] }
     }
 
     pub fn get_layers(&mut self) -> &mut Vec<Box<dyn Layer>> {
         &mut self.layers
     }
 
+    pub fn get_enabled_layers(&mut self) -> impl DoubleEndedIterator<Item = &mut Box<dyn Layer>> {
+        let disabled = &self.disabled;
+        self.layers.iter_mut().filter(move |layer| !disabled.iter().any(|name| name == layer.get_name()))
+    }
+
+    pub fn get_names(&self) -> Vec<&str> {
+        self.layers.iter().map(|layer| layer.get_name()).collect()
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.iter().any(|disabled| disabled == name)
+    }
+
+    // Returns false if there is no layer with that name
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        if !self.layers.iter().any(|layer| layer.get_name() == name) {
+            return false;
+        }
+
+        self.disabled.retain(|disabled| disabled != name);
+        if !enabled {
+            self.disabled.push(name.to_string());
+        }
+
+        true
+    }
+
     pub fn push_layer(&mut self, layer: Box<dyn Layer>) {
         self.layers.insert(self.layer_insert, layer);
         self.layer_insert += 1
@@ -0,0 +1,149 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Mutex
+    },
+    thread::{self, JoinHandle}
+};
+
+use log::Level;
+
+use super::{LogRecord, Sink};
+
+// What happens with a record when the queue of the logging thread is full
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    // Wait until the logging thread makes room
+    Block,
+    // Discard the record silently
+    Drop,
+    // Discard the record and log how many were lost once there is room again
+    CountDropped
+}
+
+enum Message {
+    Record(LogRecord),
+    // Flushes the sinks and answers when everything before it was written
+    Flush(SyncSender<()>)
+}
+
+// Where the logger sends the records, directly to the sinks or through a background thread
+pub enum Backend {
+    Sync(Vec<Mutex<Box<dyn Sink>>>),
+    Async(AsyncBackend)
+}
+
+impl Backend {
+    pub fn write(&self, record: LogRecord) {
+        match self {
+            Backend::Sync(sinks) => {
+                for sink in sinks {
+                    if let Ok(mut sink) = sink.lock() {
+                        sink.write(&record);
+                    }
+                }
+            },
+            Backend::Async(backend) => backend.write(record),
+        }
+    }
+
+    pub fn flush(&self) {
+        match self {
+            Backend::Sync(sinks) => {
+                for sink in sinks {
+                    if let Ok(mut sink) = sink.lock() {
+                        sink.flush();
+                    }
+                }
+            },
+            Backend::Async(backend) => backend.flush(),
+        }
+    }
+}
+
+pub struct AsyncBackend {
+    sender: SyncSender<Message>,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+    _worker: JoinHandle<()>
+}
+
+impl AsyncBackend {
+    pub fn new(sinks: Vec<Box<dyn Sink>>, capacity: usize, policy: OverflowPolicy) -> AsyncBackend {
+        let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+
+        let worker = thread::Builder::new()
+            .name("hades-logger".to_string())
+            .spawn(move || Self::run(sinks, receiver))
+            .expect("Failed to spawn the logging thread");
+
+        AsyncBackend { sender, policy, dropped: AtomicU64::new(0), _worker: worker }
+    }
+
+    fn run(mut sinks: Vec<Box<dyn Sink>>, receiver: Receiver<Message>) {
+        // Ends when the logger is dropped and the channel closes
+        for message in receiver {
+            match message {
+                Message::Record(record) => {
+                    for sink in sinks.iter_mut() {
+                        sink.write(&record);
+                    }
+                },
+                Message::Flush(done) => {
+                    for sink in sinks.iter_mut() {
+                        sink.flush();
+                    }
+
+                    let _ = done.send(());
+                },
+            }
+        }
+
+        for sink in sinks.iter_mut() {
+            sink.flush();
+        }
+    }
+
+    fn write(&self, record: LogRecord) {
+        if self.policy == OverflowPolicy::Block {
+            let _ = self.sender.send(Message::Record(record));
+            return;
+        }
+
+        match self.sender.try_send(Message::Record(record)) {
+            Ok(()) => self.report_dropped(),
+            Err(TrySendError::Full(_)) => if self.policy == OverflowPolicy::CountDropped {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            },
+            Err(TrySendError::Disconnected(_)) => (),
+        }
+    }
+
+    fn report_dropped(&self) {
+        let dropped = self.dropped.swap(0, Ordering::Relaxed);
+        if dropped == 0 {
+            return;
+        }
+
+        let message = format!("Logging queue full, {} records were dropped", dropped);
+        let record = LogRecord::from_message(Level::Warn, "HADES", message);
+
+        if self.sender.try_send(Message::Record(record)).is_err() {
+            self.dropped.fetch_add(dropped, Ordering::Relaxed);
+        }
+    }
+
+    // Blocks until every record queued so far reached the sinks
+    fn flush(&self) {
+        // The flush from the logging thread itself would wait on its own queue
+        if thread::current().name() == Some("hades-logger") {
+            return;
+        }
+
+        let (done, wait) = mpsc::sync_channel(1);
+        if self.sender.send(Message::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    backtrace::Backtrace,
+    fmt::Write,
+    fs,
+    panic::{self, PanicHookInfo},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread
+};
+
+use time::{format_description::FormatItem, OffsetDateTime};
+
+use super::{format::local_offset, history};
+
+const REPORT_NAME_FORMAT: &[FormatItem] =
+    time::macros::format_description!("[year][month][day]_[hour][minute][second]");
+
+// Values that describe the state of the engine, written in the crash reports
+static CRASH_INFO: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+pub fn set_crash_info(key: &str, value: impl Into<String>) {
+    if let Ok(mut info) = CRASH_INFO.lock() {
+        let value = value.into();
+
+        match info.iter_mut().find(|(name, _)| name == key) {
+            Some((_, current)) => *current = value,
+            None => info.push((key.to_string(), value)),
+        }
+    }
+}
+
+// Logs the panics through the hades logger and writes a crash report in the directory
+pub fn install_panic_hook(report_dir: Option<PathBuf>) {
+    let previous_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let backtrace = Backtrace::force_capture();
+        let message = panic_message(info);
+
+        log::log!(target: "HADES", log::Level::Error, "{}\n{}", message, backtrace);
+
+        // Records still queued in the logging thread would be lost when the process dies
+        log::logger().flush();
+
+        if let Some(dir) = &report_dir {
+            match write_report(dir, &message, &backtrace) {
+                Ok(path) => eprintln!("Crash report written to {}", path.display()),
+                Err(err) => eprintln!("Failed to write the crash report: {}", err),
+            }
+        }
+
+        previous_hook(info);
+    }));
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    let reason = payload.downcast_ref::<&str>().copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Unknown panic payload");
+
+    let thread = thread::current();
+    let location = info.location().map_or("unknown location".to_string(), |location| location.to_string());
+
+    format!("Thread '{}' panicked at {}: {}", thread.name().unwrap_or("unnamed"), location, reason)
+}
+
+fn write_report(dir: &Path, message: &str, backtrace: &Backtrace) -> std::io::Result<PathBuf> {
+    let now = OffsetDateTime::now_utc().to_offset(local_offset());
+    let name = now.format(REPORT_NAME_FORMAT).unwrap_or_else(|_| "unknown".to_string());
+
+    let mut report = String::new();
+    let _ = writeln!(report, "Hades crash report");
+    let _ = writeln!(report, "Engine version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "Time: {}", now);
+    let _ = writeln!(report, "\n{}\n\nBacktrace:\n{}", message, backtrace);
+
+    // The lock can be taken by the panicking thread, don't wait for it
+    report.push_str("\nEngine state:\n");
+    match CRASH_INFO.try_lock() {
+        Ok(info) => for (key, value) in info.iter() {
+            let _ = writeln!(report, "{}: {}", key, value);
+        },
+        Err(_) => report.push_str("Unavailable\n"),
+    }
+
+    report.push_str("\nRecent logs:\n");
+    let logs = history::try_with_history(|history| {
+        history.get_records().iter().map(|record| record.format(false) + "\n").collect::<String>()
+    });
+    report.push_str(logs.as_deref().unwrap_or("Unavailable\n"));
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("crash_{}.txt", name));
+    fs::write(&path, report)?;
+
+    Ok(path)
+}
@@ -0,0 +1,166 @@
+use std::sync::RwLock;
+
+use log::{Level, LevelFilter};
+
+// Filters shared by the installed logger, they can be changed while the application runs
+static FILTERS: RwLock<LevelFilters> = RwLock::new(LevelFilters::new());
+
+#[derive(Clone)]
+pub struct LevelFilters {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>
+}
+
+impl Default for LevelFilters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LevelFilters {
+    pub const fn new() -> LevelFilters {
+        LevelFilters { default: LevelFilter::Trace, targets: Vec::new() }
+    }
+
+    // Parses a list like "HADES=info,APP=trace,warn", an entry without target sets the default level
+    pub fn parse(&mut self, spec: &str) {
+        for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            match entry.split_once('=') {
+                Some((target, level)) => match level.trim().parse() {
+                    Ok(level) => self.set_level(target.trim(), level),
+                    Err(_) => eprintln!("Invalid log level {} for target {}", level, target),
+                },
+                None => match entry.parse() {
+                    Ok(level) => self.default = level,
+                    Err(_) => eprintln!("Invalid log level {}", entry),
+                },
+            }
+        }
+    }
+
+    pub fn get_default(&self) -> LevelFilter {
+        self.default
+    }
+
+    pub fn set_default(&mut self, level: LevelFilter) {
+        self.default = level
+    }
+
+    pub fn get_targets(&self) -> &[(String, LevelFilter)] {
+        &self.targets
+    }
+
+    pub fn set_level(&mut self, target: &str, level: LevelFilter) {
+        match self.targets.iter_mut().find(|(name, _)| name == target) {
+            Some((_, current)) => *current = level,
+            None => self.targets.push((target.to_string(), level)),
+        }
+    }
+
+    // The most specific target wins, "hades::window" matches both "hades::window" and "hades"
+    pub fn get_level(&self, target: &str) -> LevelFilter {
+        self.get_record_level(target, None)
+    }
+
+    // Same as get_level but the module path of the record matches too, the engine macros
+    // all log with the HADES or APP target so the modules are what tells them apart
+    pub fn get_record_level(&self, target: &str, module: Option<&str>) -> LevelFilter {
+        self.targets.iter()
+            .filter(|(name, _)| is_under(target, name) || module.is_some_and(|module| is_under(module, name)))
+            .max_by_key(|(name, _)| name.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    pub fn enabled(&self, target: &str, module: Option<&str>, level: Level) -> bool {
+        level <= self.get_record_level(target, module)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.targets.iter().map(|(_, level)| *level).fold(self.default, Ord::max)
+    }
+}
+
+pub fn get_filters() -> LevelFilters {
+    FILTERS.read().map(|filters| filters.clone()).unwrap_or_default()
+}
+
+pub fn set_filters(filters: LevelFilters) {
+    log::set_max_level(filters.max_level());
+
+    if let Ok(mut current) = FILTERS.write() {
+        *current = filters;
+    }
+}
+
+pub fn set_default_level(level: LevelFilter) {
+    let mut filters = get_filters();
+    filters.set_default(level);
+    set_filters(filters);
+}
+
+pub fn set_target_level(target: &str, level: LevelFilter) {
+    let mut filters = get_filters();
+    filters.set_level(target, level);
+    set_filters(filters);
+}
+
+pub(crate) fn enabled(target: &str, module: Option<&str>, level: Level) -> bool {
+    FILTERS.read().is_ok_and(|filters| filters.enabled(target, module, level))
+}
+
+// The path is the name or one of its "::" children
+fn is_under(path: &str, name: &str) -> bool {
+    path == name || path.strip_prefix(name).is_some_and(|rest| rest.starts_with("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_targets_and_default() {
+        let mut filters = LevelFilters::new();
+        filters.parse("HADES=info, APP = trace,warn,,BROKEN=loud");
+
+        assert_eq!(filters.get_default(), LevelFilter::Warn);
+        assert_eq!(filters.get_level("HADES"), LevelFilter::Info);
+        assert_eq!(filters.get_level("APP"), LevelFilter::Trace);
+        assert_eq!(filters.get_level("BROKEN"), LevelFilter::Warn);
+        assert_eq!(filters.get_targets().len(), 2);
+    }
+
+    #[test]
+    fn parse_replaces_repeated_targets() {
+        let mut filters = LevelFilters::new();
+        filters.parse("HADES=info");
+        filters.parse("HADES=error");
+
+        assert_eq!(filters.get_targets(), &[("HADES".to_string(), LevelFilter::Error)]);
+    }
+
+    #[test]
+    fn most_specific_target_wins() {
+        let mut filters = LevelFilters::new();
+        filters.parse("off,hades=info,hades::window=trace");
+
+        assert_eq!(filters.get_level("hades::window"), LevelFilter::Trace);
+        assert_eq!(filters.get_level("hades::window::monitor"), LevelFilter::Trace);
+        assert_eq!(filters.get_level("hades::renderer"), LevelFilter::Info);
+        // Only whole path segments match
+        assert_eq!(filters.get_level("hadesx"), LevelFilter::Off);
+        assert!(filters.enabled("hades", None, Level::Info));
+        assert!(!filters.enabled("hades", None, Level::Debug));
+    }
+
+    #[test]
+    fn module_paths_match_the_engine_records() {
+        let mut filters = LevelFilters::new();
+        filters.parse("HADES=info,hades::renderer=debug,hades::renderer::shader=error");
+
+        assert_eq!(filters.get_record_level("HADES", Some("hades::renderer::texture")), LevelFilter::Debug);
+        assert_eq!(filters.get_record_level("HADES", Some("hades::renderer::shader")), LevelFilter::Error);
+        assert_eq!(filters.get_record_level("HADES", Some("hades::window")), LevelFilter::Info);
+        assert_eq!(filters.get_record_level("HADES", None), LevelFilter::Info);
+        assert!(filters.enabled("HADES", Some("hades::renderer"), Level::Debug));
+    }
+}
@@ -0,0 +1,372 @@
+use std::{
+    fmt::{self, Display, Write},
+    sync::OnceLock
+};
+
+use colored::*;
+use log::{kv::Value, Level};
+use time::{format_description::{well_known::Rfc3339, FormatItem}, OffsetDateTime, UtcOffset};
+
+use super::{sinks::strip_ansi, LogRecord};
+
+const SECONDS_FORMAT: &[FormatItem] =
+    time::macros::format_description!("[hour]:[minute]:[second]");
+const MILLIS_FORMAT: &[FormatItem] =
+    time::macros::format_description!("[hour]:[minute]:[second].[subsecond digits:3]");
+const MICROS_FORMAT: &[FormatItem] =
+    time::macros::format_description!("[hour]:[minute]:[second].[subsecond digits:6]");
+
+pub const DEFAULT_PATTERN: &str = "[{time}] [{level}] {target} - {msg}{fields}";
+
+static LOCAL_OFFSET: OnceLock<UtcOffset> = OnceLock::new();
+
+// The offset can only be read safely while the process has a single thread,
+// so it is captured once when the logger is created
+pub fn local_offset() -> UtcOffset {
+    *LOCAL_OFFSET.get_or_init(|| UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
+}
+
+#[derive(Clone)]
+pub enum LogFormat {
+    // Line built from a pattern like "[{time}] [{level}] {target} - {msg}"
+    Text(TextFormat),
+    // One JSON object per line
+    Json
+}
+
+impl LogFormat {
+    pub fn text() -> LogFormat {
+        LogFormat::Text(TextFormat::default())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TimePrecision {
+    Seconds,
+    Millis,
+    Micros
+}
+
+#[derive(Clone)]
+enum PatternToken {
+    Literal(String),
+    Time,
+    Elapsed,
+    Level,
+    Target,
+    Module,
+    File,
+    Line,
+    Thread,
+    Frame,
+    Message,
+    Fields
+}
+
+// Pattern of the text lines, the placeholders are:
+// {time} {elapsed} {level} {target} {module} {file} {line} {thread} {frame} {msg} {fields}
+// {fields} writes " key=value" for each structured field, nothing when there are none
+#[derive(Clone)]
+pub struct TextFormat {
+    tokens: Vec<PatternToken>,
+    precision: TimePrecision,
+    local_time: bool
+}
+
+impl Default for TextFormat {
+    fn default() -> TextFormat {
+        TextFormat::new(DEFAULT_PATTERN)
+    }
+}
+
+impl TextFormat {
+    pub fn new(pattern: &str) -> TextFormat {
+        TextFormat { tokens: Self::parse(pattern), precision: TimePrecision::Seconds, local_time: true }
+    }
+
+    pub fn with_precision(mut self, precision: TimePrecision) -> TextFormat {
+        self.precision = precision;
+        self
+    }
+
+    // Show the time in the local time zone instead of UTC
+    pub fn with_local_time(mut self, local_time: bool) -> TextFormat {
+        self.local_time = local_time;
+        self
+    }
+
+    fn parse(pattern: &str) -> Vec<PatternToken> {
+        let mut tokens = vec![];
+        let mut literal = String::new();
+        let mut rest = pattern;
+
+        while let Some(start) = rest.find('{') {
+            literal.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let end = match rest.find('}') {
+                Some(end) => end,
+                None => break,
+            };
+
+            let token = match &rest[1..end] {
+                "time"    => PatternToken::Time,
+                "elapsed" => PatternToken::Elapsed,
+                "level"   => PatternToken::Level,
+                "target"  => PatternToken::Target,
+                "module"  => PatternToken::Module,
+                "file"    => PatternToken::File,
+                "line"    => PatternToken::Line,
+                "thread"  => PatternToken::Thread,
+                "frame"   => PatternToken::Frame,
+                "msg"     => PatternToken::Message,
+                "fields"  => PatternToken::Fields,
+                // Unknown placeholders are kept as they are written
+                _ => {
+                    literal.push_str(&rest[..=end]);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            };
+
+            if !literal.is_empty() {
+                tokens.push(PatternToken::Literal(std::mem::take(&mut literal)));
+            }
+
+            tokens.push(token);
+            rest = &rest[end + 1..];
+        }
+
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            tokens.push(PatternToken::Literal(literal));
+        }
+
+        tokens
+    }
+
+    fn format_time(&self, timestamp: OffsetDateTime) -> String {
+        let timestamp = if self.local_time { timestamp.to_offset(local_offset()) } else { timestamp };
+
+        let description = match self.precision {
+            TimePrecision::Seconds => SECONDS_FORMAT,
+            TimePrecision::Millis => MILLIS_FORMAT,
+            TimePrecision::Micros => MICROS_FORMAT,
+        };
+
+        timestamp.format(description).expect("Error with time stamp format")
+    }
+
+    pub fn format(&self, record: &LogRecord, colored: bool) -> String {
+        let mut line = String::with_capacity(128);
+
+        for token in &self.tokens {
+            let _ = match token {
+                PatternToken::Literal(text) => write!(line, "{}", text),
+                PatternToken::Time => write!(line, "{}", self.format_time(record.get_timestamp())),
+                PatternToken::Elapsed => {
+                    let elapsed = record.get_elapsed().as_secs_f64();
+                    match self.precision {
+                        TimePrecision::Seconds => write!(line, "{:.0}s", elapsed),
+                        TimePrecision::Millis => write!(line, "{:.3}s", elapsed),
+                        TimePrecision::Micros => write!(line, "{:.6}s", elapsed),
+                    }
+                },
+                PatternToken::Level => write!(line, "{}", level_string(record.get_level(), colored)),
+                PatternToken::Target => write!(line, "{}", record.get_target()),
+                PatternToken::Module => write!(line, "{}", record.get_module().unwrap_or("?")),
+                PatternToken::File => write!(line, "{}", record.get_file().unwrap_or("?")),
+                PatternToken::Line => write!(line, "{}", record.get_line().unwrap_or(0)),
+                PatternToken::Thread => write!(line, "{}", record.get_thread().unwrap_or("unnamed")),
+                PatternToken::Frame => write!(line, "{}", record.get_frame()),
+                PatternToken::Message => write!(line, "{}", record.get_message()),
+                PatternToken::Fields => {
+                    for (key, value) in record.get_fields() {
+                        let _ = write!(line, " {}={}", key, value);
+                    }
+                    Ok(())
+                },
+            };
+        }
+
+        line
+    }
+}
+
+fn level_string(level: Level, colored: bool) -> String {
+    if !colored {
+        return level.to_string();
+    }
+
+    // Find the correct color for the level of the log
+    match level {
+        Level::Error => format!("{:<5}", level.to_string()).red().to_string(),
+        Level::Warn => format!("{:<4}", level.to_string()).yellow().to_string(),
+        Level::Info => format!("{:<4}", level.to_string()).normal().to_string(),
+        Level::Debug => format!("{:<5}", level.to_string()).blue().to_string(),
+        Level::Trace => format!("{:<5}", level.to_string()).cyan().to_string()
+    }
+}
+
+#[derive(Clone)]
+pub enum FieldValue {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Str(String)
+}
+
+impl FieldValue {
+    pub fn from_value(value: &Value) -> FieldValue {
+        if let Some(value) = value.to_bool() {
+            FieldValue::Bool(value)
+        }
+        else if let Some(value) = value.to_i64() {
+            FieldValue::Int(value)
+        }
+        else if let Some(value) = value.to_u64() {
+            FieldValue::UInt(value)
+        }
+        else if let Some(value) = value.to_f64() {
+            FieldValue::Float(value)
+        }
+        else {
+            FieldValue::Str(value.to_string())
+        }
+    }
+
+    fn write_json(&self, out: &mut String) {
+        match self {
+            FieldValue::Str(value) => write_json_string(out, value),
+            FieldValue::Float(value) if !value.is_finite() => write_json_string(out, &value.to_string()),
+            value => { let _ = write!(out, "{}", value); },
+        }
+    }
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Bool(value)  => write!(f, "{}", value),
+            FieldValue::Int(value)   => write!(f, "{}", value),
+            FieldValue::UInt(value)  => write!(f, "{}", value),
+            FieldValue::Float(value) => write!(f, "{}", value),
+            FieldValue::Str(value)   => write!(f, "{}", value)
+        }
+    }
+}
+
+impl LogRecord {
+    pub fn format_as(&self, format: &LogFormat, colored: bool) -> String {
+        match format {
+            LogFormat::Text(text_format) => text_format.format(self, colored),
+            LogFormat::Json => self.format_json(),
+        }
+    }
+
+    // Text line of the record with the default pattern, the level is colored only for terminals
+    pub fn format(&self, colored: bool) -> String {
+        static DEFAULT_FORMAT: OnceLock<TextFormat> = OnceLock::new();
+        DEFAULT_FORMAT.get_or_init(TextFormat::default).format(self, colored)
+    }
+
+    pub fn format_json(&self) -> String {
+        let mut out = String::with_capacity(256);
+
+        let timestamp = self.get_timestamp().format(&Rfc3339)
+            .unwrap_or_else(|_| OffsetDateTime::UNIX_EPOCH.to_string());
+
+        out.push_str("{\"timestamp\":");
+        write_json_string(&mut out, &timestamp);
+        out.push_str(",\"level\":");
+        write_json_string(&mut out, self.get_level().as_str());
+        out.push_str(",\"target\":");
+        write_json_string(&mut out, self.get_target());
+
+        if let Some(module) = self.get_module() {
+            out.push_str(",\"module\":");
+            write_json_string(&mut out, module);
+        }
+
+        if let Some(file) = self.get_file() {
+            out.push_str(",\"file\":");
+            write_json_string(&mut out, file);
+        }
+
+        if let Some(line) = self.get_line() {
+            let _ = write!(out, ",\"line\":{}", line);
+        }
+
+        if let Some(thread) = self.get_thread() {
+            out.push_str(",\"thread\":");
+            write_json_string(&mut out, thread);
+        }
+
+        let _ = write!(out, ",\"frame\":{},\"elapsed\":{}", self.get_frame(), self.get_elapsed().as_secs_f64());
+
+        out.push_str(",\"message\":");
+        write_json_string(&mut out, &strip_ansi(self.get_message()));
+
+        if !self.get_fields().is_empty() {
+            out.push_str(",\"fields\":{");
+
+            for (index, (key, value)) in self.get_fields().iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+
+                write_json_string(&mut out, key);
+                out.push(':');
+                value.write_json(&mut out);
+            }
+
+            out.push('}');
+        }
+
+        out.push('}');
+        out
+    }
+}
+
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"'  => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); },
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(pattern: &str) -> String {
+        let record = LogRecord::from_message(Level::Warn, "APP", "Hello".to_string());
+        TextFormat::new(pattern).format(&record, false)
+    }
+
+    #[test]
+    fn parse_placeholders() {
+        assert_eq!(format("[{level}] {target} - {msg}{fields}"), "[WARN] APP - Hello");
+        assert_eq!(format("{msg}{msg}"), "HelloHello");
+        assert_eq!(format("no placeholders"), "no placeholders");
+    }
+
+    #[test]
+    fn parse_keeps_unknown_and_unclosed_placeholders() {
+        assert_eq!(format("{unknown} {msg}"), "{unknown} Hello");
+        assert_eq!(format("{msg} {level"), "Hello {level");
+        assert_eq!(format("{}"), "{}");
+    }
+}
@@ -0,0 +1,79 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use super::{LogRecord, Sink};
+
+// Recent records kept in memory for the engine tools, like the log console or the crash reports
+static HISTORY: Mutex<History> = Mutex::new(History::new());
+
+pub struct History {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+    // Records received since the last clear, used by the readers to know when there is new data
+    total: u64
+}
+
+impl History {
+    const fn new() -> History {
+        History { records: VecDeque::new(), capacity: 0, total: 0 }
+    }
+
+    pub fn get_records(&self) -> &VecDeque<LogRecord> {
+        &self.records
+    }
+
+    pub fn get_total(&self) -> u64 {
+        self.total
+    }
+
+    fn push(&mut self, record: LogRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        while self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+
+        self.records.push_back(record);
+        self.total += 1;
+    }
+}
+
+// Sink that copies the records into the shared history
+pub struct HistorySink;
+
+impl HistorySink {
+    pub fn new(capacity: usize) -> HistorySink {
+        if let Ok(mut history) = HISTORY.lock() {
+            history.capacity = capacity;
+            history.records.reserve(capacity);
+        }
+
+        HistorySink
+    }
+}
+
+impl Sink for HistorySink {
+    fn write(&mut self, record: &LogRecord) {
+        if let Ok(mut history) = HISTORY.lock() {
+            history.push(record.clone());
+        }
+    }
+}
+
+// Gives access to the history without copying it, keep the closure short as it blocks the logger
+pub fn with_history<R>(f: impl FnOnce(&History) -> R) -> Option<R> {
+    HISTORY.lock().ok().map(|history| f(&history))
+}
+
+// Same as with_history but gives up if the lock is taken, used where waiting could deadlock
+pub(crate) fn try_with_history<R>(f: impl FnOnce(&History) -> R) -> Option<R> {
+    HISTORY.try_lock().ok().map(|history| f(&history))
+}
+
+pub fn clear_history() {
+    if let Ok(mut history) = HISTORY.lock() {
+        history.records.clear();
+        history.total = 0;
+    }
+}
@@ -0,0 +1,421 @@
+pub mod backend;
+pub mod crash;
+pub mod filter;
+pub mod format;
+pub mod history;
+pub mod sinks;
+
+use std::{
+    env, io,
+    path::PathBuf,
+    sync::{atomic::{AtomicU64, Ordering}, Mutex, OnceLock},
+    thread,
+    time::{Duration, Instant}
+};
+
+use log::{
+    kv::{self, Key, Value, VisitSource},
+    Level, LevelFilter, Log, Metadata, Record, SetLoggerError
+};
+use time::OffsetDateTime;
+
+pub use backend::OverflowPolicy;
+pub use crash::set_crash_info;
+pub use format::{FieldValue, LogFormat, TextFormat, TimePrecision};
+pub use history::{clear_history, with_history, History, HistorySink};
+
+use backend::{AsyncBackend, Backend};
+pub use filter::{get_filters, set_default_level, set_filters, set_target_level, LevelFilters};
+pub use sinks::{ConsoleSink, FileRotation, FileSink, Sink};
+
+// Environment variable with the level filters, for example HADES_LOG=HADES=info,APP=trace
+pub const LOG_ENV_VAR: &str = "HADES_LOG";
+
+static START: OnceLock<Instant> = OnceLock::new();
+static FRAME: AtomicU64 = AtomicU64::new(0);
+
+fn elapsed() -> Duration {
+    START.get_or_init(Instant::now).elapsed()
+}
+
+// Frame number attached to the records, the application updates it every frame
+pub fn set_frame(frame: u64) {
+    FRAME.store(frame, Ordering::Relaxed);
+}
+
+// Owned copy of a log record that is handed to every sink
+#[derive(Clone)]
+pub struct LogRecord {
+    level: Level,
+    target: String,
+    message: String,
+    timestamp: OffsetDateTime,
+    elapsed: Duration,
+    frame: u64,
+    module: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    thread: Option<String>,
+    fields: Vec<(String, FieldValue)>
+}
+
+// Collects the key-value pairs attached to a record
+struct FieldCollector(Vec<(String, FieldValue)>);
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.push((key.to_string(), FieldValue::from_value(&value)));
+        Ok(())
+    }
+}
+
+impl LogRecord {
+    pub fn new(record: &Record) -> LogRecord {
+        let mut fields = FieldCollector(vec![]);
+        let _ = record.key_values().visit(&mut fields);
+
+        LogRecord {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            timestamp: OffsetDateTime::now_utc(),
+            elapsed: elapsed(),
+            frame: FRAME.load(Ordering::Relaxed),
+            module: record.module_path().map(str::to_string),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+            thread: thread::current().name().map(str::to_string),
+            fields: fields.0
+        }
+    }
+
+    // Record created by the engine itself instead of coming from the log macros
+    pub fn from_message(level: Level, target: &str, message: String) -> LogRecord {
+        LogRecord {
+            level,
+            target: target.to_string(),
+            message,
+            timestamp: OffsetDateTime::now_utc(),
+            elapsed: elapsed(),
+            frame: FRAME.load(Ordering::Relaxed),
+            module: None,
+            file: None,
+            line: None,
+            thread: thread::current().name().map(str::to_string),
+            fields: vec![]
+        }
+    }
+
+    pub fn get_level(&self) -> Level {
+        self.level
+    }
+
+    pub fn get_target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_timestamp(&self) -> OffsetDateTime {
+        self.timestamp
+    }
+
+    // Time since the logger was created
+    pub fn get_elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn get_frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn get_module(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+
+    pub fn get_file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    pub fn get_line(&self) -> Option<u32> {
+        self.line
+    }
+
+    pub fn get_thread(&self) -> Option<&str> {
+        self.thread.as_deref()
+    }
+
+    pub fn get_fields(&self) -> &[(String, FieldValue)] {
+        &self.fields
+    }
+}
+
+pub struct Logger {
+    backend: Backend,
+    filters: LevelFilters,
+    crash_report_dir: Option<PathBuf>
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Logger {
+    // Logger with only the colored console output
+    pub fn new() -> Logger {
+        format::local_offset();
+        elapsed();
+
+        Logger { backend: Backend::Sync(vec![Mutex::new(Box::new(ConsoleSink::new(true)))]), filters: LevelFilters::new(), crash_report_dir: None }
+    }
+
+    pub fn builder() -> LoggerBuilder {
+        LoggerBuilder::new()
+    }
+
+    pub fn init(mut self) -> Result<(), SetLoggerError> {
+        let crash_report_dir = self.crash_report_dir.take();
+
+        filter::set_filters(self.filters.clone());
+        log::set_boxed_logger(Box::new(self))?;
+
+        crash::install_panic_hook(crash_report_dir);
+
+        Ok(())
+    }
+}
+
+impl Log for Logger {
+    // The metadata has no module path, the module filters are only checked in log
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        filter::enabled(metadata.target(), None, metadata.level())
+    }
+
+    fn log(&self, record: &Record) {
+        if filter::enabled(record.target(), record.module_path(), record.level()) {
+            self.backend.write(LogRecord::new(record));
+        }
+    }
+
+    fn flush(&self) {
+        self.backend.flush();
+    }
+}
+
+pub struct LoggerBuilder {
+    console: Option<bool>,
+    console_format: LogFormat,
+    stderr_level: Option<Level>,
+    files: Vec<(PathBuf, FileRotation)>,
+    file_format: LogFormat,
+    sinks: Vec<Box<dyn Sink>>,
+    filters: LevelFilters,
+    asynchronous: Option<(usize, OverflowPolicy)>,
+    history: Option<usize>,
+    crash_report_dir: Option<PathBuf>
+}
+
+impl Default for LoggerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoggerBuilder {
+    pub fn new() -> LoggerBuilder {
+        LoggerBuilder {
+            console: None, console_format: LogFormat::text(), stderr_level: None,
+            files: vec![], file_format: LogFormat::text(), sinks: vec![], filters: LevelFilters::new(),
+            asynchronous: None, history: None, crash_report_dir: None
+        }
+    }
+
+    // Print the logs in the terminal, with or without colors
+    pub fn console(mut self, colored: bool) -> LoggerBuilder {
+        self.console = Some(colored);
+        self
+    }
+
+    // Console records of this level or more severe go to stderr instead of stdout
+    pub fn stderr(mut self, level: Level) -> LoggerBuilder {
+        self.stderr_level = Some(level);
+        self
+    }
+
+    pub fn console_format(mut self, format: LogFormat) -> LoggerBuilder {
+        self.console_format = format;
+        self
+    }
+
+    pub fn file(mut self, path: impl Into<PathBuf>, rotation: FileRotation) -> LoggerBuilder {
+        self.files.push((path.into(), rotation));
+        self
+    }
+
+    // Format of every file added with the builder
+    pub fn file_format(mut self, format: LogFormat) -> LoggerBuilder {
+        self.file_format = format;
+        self
+    }
+
+    pub fn sink(mut self, sink: Box<dyn Sink>) -> LoggerBuilder {
+        self.sinks.push(sink);
+        self
+    }
+
+    // Keep the last records in memory for the log console
+    pub fn history(mut self, capacity: usize) -> LoggerBuilder {
+        self.history = Some(capacity);
+        self
+    }
+
+    // Write a report with the backtrace, engine state and recent logs when the application panics
+    pub fn crash_reports(mut self, dir: impl Into<PathBuf>) -> LoggerBuilder {
+        self.crash_report_dir = Some(dir.into());
+        self
+    }
+
+    // Level used by the targets without their own filter
+    pub fn level(mut self, level: LevelFilter) -> LoggerBuilder {
+        self.filters.set_default(level);
+        self
+    }
+
+    // Level of a target like HADES, APP or a module path
+    pub fn target_level(mut self, target: &str, level: LevelFilter) -> LoggerBuilder {
+        self.filters.set_level(target, level);
+        self
+    }
+
+    // Write the records from a background thread, queueing up to capacity records
+    pub fn asynchronous(mut self, capacity: usize, policy: OverflowPolicy) -> LoggerBuilder {
+        self.asynchronous = Some((capacity, policy));
+        self
+    }
+
+    pub fn build(mut self) -> io::Result<Logger> {
+        // Read before the logging thread exists, see format::local_offset
+        format::local_offset();
+        elapsed();
+
+        // The environment overrides what was configured in code
+        if let Ok(spec) = env::var(LOG_ENV_VAR) {
+            self.filters.parse(&spec);
+        }
+
+        let mut sinks: Vec<Box<dyn Sink>> = vec![];
+
+        if let Some(colored) = self.console {
+            let mut console = ConsoleSink::new(colored).with_format(self.console_format.clone());
+            if let Some(level) = self.stderr_level {
+                console = console.with_stderr_level(level);
+            }
+
+            sinks.push(Box::new(console));
+        }
+
+        for (path, rotation) in self.files {
+            sinks.push(Box::new(FileSink::new(path, rotation)?.with_format(self.file_format.clone())));
+        }
+
+        if let Some(capacity) = self.history {
+            sinks.push(Box::new(HistorySink::new(capacity)));
+        }
+
+        sinks.extend(self.sinks);
+
+        let backend = match self.asynchronous {
+            Some((capacity, policy)) => Backend::Async(AsyncBackend::new(sinks, capacity, policy)),
+            None => Backend::Sync(sinks.into_iter().map(Mutex::new).collect()),
+        };
+
+        Ok(Logger { backend, filters: self.filters, crash_report_dir: self.crash_report_dir })
+    }
+}
+
+pub fn init() -> Result<(), SetLoggerError>{
+    Logger::new().init()
+}
+
+// Waits until every pending record is written, call it before the process exits
+pub fn shutdown() {
+    log::logger().flush();
+}
+
+// Core engine macros for the logs
+// Structured fields go before the message: hds_core_info!(width = 1280, height = 720; "Resized")
+// The message doesn't repeat them, the {fields} of the format write them after it
+#[allow(unused_macros)]
+macro_rules! hds_core_error {
+    ($($arg:tt)+) => (log::log!(target: "HADES", log::Level::Error, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_core_error;
+
+#[allow(unused_macros)]
+macro_rules! hds_core_warn {
+    ($($arg:tt)+) => (log::log!(target: "HADES", log::Level::Warn, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_core_warn;
+
+#[allow(unused_macros)]
+macro_rules! hds_core_info {
+    ($($arg:tt)+) => (log::log!(target: "HADES", log::Level::Info, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_core_info;
+
+#[allow(unused_macros)]
+macro_rules! hds_core_debug {
+    ($($arg:tt)+) => (log::log!(target: "HADES", log::Level::Debug, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_core_debug;
+
+#[allow(unused_macros)]
+macro_rules! hds_core_trace {
+    ($($arg:tt)+) => (log::log!(target: "HADES", log::Level::Trace, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_core_trace;
+
+// App macros for the logs
+#[allow(unused_macros)]
+macro_rules! hds_error {
+    ($($arg:tt)+) => (log::log!(target: "APP", log::Level::Error, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_error;
+
+#[allow(unused_macros)]
+macro_rules! hds_warn {
+    ($($arg:tt)+) => (log::log!(target: "APP", log::Level::Warn, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_warn;
+
+#[allow(unused_macros)]
+macro_rules! hds_info {
+    ($($arg:tt)+) => (log::log!(target: "APP", log::Level::Info, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_info;
+
+#[allow(unused_macros)]
+macro_rules! hds_debug {
+    ($($arg:tt)+) => (log::log!(target: "APP", log::Level::Debug, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_debug;
+
+#[allow(unused_macros)]
+macro_rules! hds_trace {
+    ($($arg:tt)+) => (log::log!(target: "APP", log::Level::Trace, $($arg)+));
+}
+#[allow(unused_imports)]
+pub(crate) use hds_trace;
\ No newline at end of file
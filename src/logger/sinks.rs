@@ -0,0 +1,265 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant}
+};
+
+use log::Level;
+use time::{Date, OffsetDateTime};
+
+use super::{format::local_offset, LogFormat, LogRecord};
+
+// Destination of the log records
+pub trait Sink: Send {
+    fn write(&mut self, record: &LogRecord);
+    fn flush(&mut self) {}
+}
+
+pub struct ConsoleSink {
+    colored: bool,
+    format: LogFormat,
+    stderr_level: Option<Level>
+}
+
+impl ConsoleSink {
+    pub fn new(colored: bool) -> ConsoleSink {
+        ConsoleSink { colored, format: LogFormat::text(), stderr_level: None }
+    }
+
+    pub fn with_format(mut self, format: LogFormat) -> ConsoleSink {
+        self.format = format;
+        self
+    }
+
+    // Records of this level or more severe are written to stderr
+    pub fn with_stderr_level(mut self, level: Level) -> ConsoleSink {
+        self.stderr_level = Some(level);
+        self
+    }
+}
+
+impl Sink for ConsoleSink {
+    fn write(&mut self, record: &LogRecord) {
+        let line = record.format_as(&self.format, self.colored);
+
+        // Errors writing to the terminal can't be reported anywhere
+        let _ = match self.stderr_level {
+            Some(level) if record.get_level() <= level => writeln!(io::stderr().lock(), "{}", line),
+            _ => writeln!(io::stdout().lock(), "{}", line),
+        };
+    }
+
+    fn flush(&mut self) {
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
+    }
+}
+
+pub struct FileRotation {
+    // Rotate when the file grows over this amount of bytes
+    pub max_size: Option<u64>,
+    // Rotate when the date changes
+    pub daily: bool,
+    // Amount of rotated files that are kept next to the current one
+    pub retention: usize
+}
+
+impl Default for FileRotation {
+    fn default() -> FileRotation {
+        FileRotation { max_size: Some(10 * 1024 * 1024), daily: false, retention: 5 }
+    }
+}
+
+// Wait before trying again when the file can't be rotated or opened, instead of failing on every record
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+pub struct FileSink {
+    path: PathBuf,
+    rotation: FileRotation,
+    format: LogFormat,
+    file: Option<File>,
+    size: u64,
+    // Local date, the same the text format prints
+    date: Date,
+    retry_at: Option<Instant>
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>, rotation: FileRotation) -> io::Result<FileSink> {
+        let path = path.into();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut sink = FileSink { path, rotation, format: LogFormat::text(), file: None, size: 0, date: local_date(OffsetDateTime::now_utc()), retry_at: None };
+
+        // Logs left by a previous day are rotated before writing the new ones
+        if let Ok(modified) = fs::metadata(&sink.path).and_then(|metadata| metadata.modified()) {
+            if sink.rotation.daily && local_date(OffsetDateTime::from(modified)) != sink.date {
+                sink.rotate()?;
+            }
+        }
+
+        sink.open()?;
+        Ok(sink)
+    }
+
+    pub fn with_format(mut self, format: LogFormat) -> FileSink {
+        self.format = format;
+        self
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = file.metadata()?.len();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    // Moves log -> log.1 -> log.2 ... dropping the files over the retention count
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+
+        if self.rotation.retention == 0 {
+            return remove_if_exists(&self.path);
+        }
+
+        remove_if_exists(&self.rotated_path(self.rotation.retention))?;
+
+        for index in (1..self.rotation.retention).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+
+        if self.path.exists() {
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        Ok(())
+    }
+
+    fn needs_rotation(&self, record: &LogRecord) -> bool {
+        let over_size = self.rotation.max_size.is_some_and(|max_size| self.size >= max_size);
+        let new_day = self.rotation.daily && local_date(record.get_timestamp()) != self.date;
+
+        over_size || new_day
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, record: &LogRecord) {
+        if self.retry_at.is_none_or(|retry_at| Instant::now() >= retry_at) {
+            self.retry_at = None;
+
+            if self.needs_rotation(record) {
+                self.date = local_date(record.get_timestamp());
+
+                if let Err(err) = self.rotate() {
+                    eprintln!("Failed to rotate log file {}: {}", self.path.display(), err);
+                    self.retry_at = Some(Instant::now() + RETRY_DELAY);
+                }
+            }
+
+            // Also after a failed rotation, the records keep going to the file that is still there
+            if self.file.is_none() {
+                if let Err(err) = self.open() {
+                    eprintln!("Failed to open log file {}: {}", self.path.display(), err);
+                    self.retry_at = Some(Instant::now() + RETRY_DELAY);
+                }
+            }
+        }
+
+        if let Some(file) = &mut self.file {
+            let line = strip_ansi(&record.format_as(&self.format, false));
+
+            if writeln!(file, "{}", line).is_ok() {
+                self.size += line.len() as u64 + 1;
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = &mut self.file {
+            let _ = file.flush();
+        }
+    }
+}
+
+fn local_date(timestamp: OffsetDateTime) -> Date {
+    timestamp.to_offset(local_offset()).date()
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+// Removes the terminal escape sequences that messages may carry
+pub fn strip_ansi(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+
+            // Skip parameters until the final byte of the sequence
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+        else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_names_and_retention() {
+        let dir = std::env::temp_dir().join(format!("hades_file_sink_{}", std::process::id()));
+        let path = dir.join("test.log");
+
+        // Every record goes over the size, so each one starts a new file
+        let rotation = FileRotation { max_size: Some(1), daily: false, retention: 2 };
+        let mut sink = FileSink::new(&path, rotation).unwrap();
+
+        for index in 0..4 {
+            sink.write(&LogRecord::from_message(Level::Info, "TEST", format!("record {}", index)));
+        }
+        sink.flush();
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).ok();
+        assert!(read("test.log").unwrap().contains("record 3"));
+        assert!(read("test.log.1").unwrap().contains("record 2"));
+        assert!(read("test.log.2").unwrap().contains("record 1"));
+        assert_eq!(read("test.log.3"), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn strip_ansi_removes_escape_sequences() {
+        assert_eq!(strip_ansi("\u{1b}[31mERROR\u{1b}[0m done"), "ERROR done");
+        assert_eq!(strip_ansi("plain [text]"), "plain [text]");
+    }
+}
@@ -1,15 +1,71 @@
 pub mod logger;
+pub mod math;
+pub mod console;
 pub mod events;
 pub mod window;
 pub mod application;
 pub mod layers;
+pub mod renderer;
+pub mod golden;
 
 use logger::*;
 use application::*;
+use window::WindowData;
 
 fn main() {
-    Logger::new().init().unwrap();
+    Logger::builder()
+        .console(true)
+        .console_format(LogFormat::Text(TextFormat::default().with_precision(TimePrecision::Millis)))
+        .file_format(LogFormat::Text(TextFormat::new("[{time}] [{elapsed}] [frame {frame}] [{thread}] [{level}] {target} - {msg}{fields}").with_precision(TimePrecision::Micros)))
+        .stderr(log::Level::Error)
+        .target_level("HADES", log::LevelFilter::Info)
+        .file("logs/hades.log", FileRotation::default())
+        .history(2048)
+        .crash_reports("crash_reports")
+        .asynchronous(8192, OverflowPolicy::CountDropped)
+        .build()
+        .expect("Failed to create the logger")
+        .init()
+        .unwrap();
 
-    let (mut app, event_loop) = Application::new();
-    app.run(event_loop)
+    // Compares the golden scenes with the reference images, --update writes the references
+    // and the other arguments select the scenes by name
+    if std::env::args().any(|arg| arg == "--golden") {
+        let update = std::env::args().any(|arg| arg == "--update");
+        let filters: Vec<String> = std::env::args().skip(1).filter(|arg| !arg.starts_with("--")).collect();
+        let tests: Vec<golden::GoldenTest> = golden::scenes::get_tests().into_iter()
+            .filter(|test| filters.is_empty() || filters.iter().any(|filter| test.name.contains(filter.as_str())))
+            .collect();
+
+        let failures = golden::GoldenHarness::new("golden", "golden_output").with_update(update).run_all(&tests);
+        logger::shutdown();
+        std::process::exit(if failures == 0 { 0 } else { 1 });
+    }
+
+    if std::env::args().any(|arg| arg == "--headless") {
+        // --gl renders the frames offscreen, the screenshots then work without a window
+        let mut app = if std::env::args().any(|arg| arg == "--gl") {
+            match Application::new_headless_with_graphics(HeadlessGraphics::default()) {
+                Ok(app) => app,
+                Err(err) => {
+                    hds_core_error!("{}", err);
+                    logger::shutdown();
+                    std::process::exit(1);
+                },
+            }
+        }
+        else {
+            Application::new_headless()
+        };
+        app.get_console().borrow_mut().load("hades.cfg");
+        app.run_headless(HeadlessSettings::default());
+        logger::shutdown();
+        return;
+    }
+
+    let window_data = WindowData::default().with_persistence("hades_window.cfg");
+    let (mut app, event_loop) = Application::new_with_window(window_data);
+    app.get_console().borrow_mut().load("hades.cfg");
+    app.run(event_loop);
+    logger::shutdown();
 }
@@ -0,0 +1,288 @@
+// Math types of the engine, the conventions are:
+// - Right handed coordinates with y up and -z forward
+// - Column major matrices that multiply column vectors, like GL expects them
+// - Clip space depth from -1 to 1, the GL default
+// - Angles in radians
+pub use glam::{IVec2, Mat3, Mat4, Quat, UVec2, Vec2, Vec3, Vec4};
+
+// Projection helpers
+
+pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+    Mat4::orthographic_rh_gl(left, right, bottom, top, near, far)
+}
+
+// Vertical field of view
+pub fn perspective(fov: f32, aspect_ratio: f32, near: f32, far: f32) -> Mat4 {
+    Mat4::perspective_rh_gl(fov, aspect_ratio, near, far)
+}
+
+pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+    Mat4::look_at_rh(eye, target, up)
+}
+
+// Window coordinates in pixels, with the origin at the top left, to normalized device coordinates
+pub fn screen_to_ndc(position: Vec2, viewport_size: Vec2) -> Vec2 {
+    Vec2::new(position.x / viewport_size.x * 2.0 - 1.0, 1.0 - position.y / viewport_size.y * 2.0)
+}
+
+// Translation, rotation and scale, applied as scale first and translation last
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform { translation: Vec3::ZERO, rotation: Quat::IDENTITY, scale: Vec3::ONE };
+
+    pub fn from_translation(translation: Vec3) -> Transform {
+        Transform { translation, ..Transform::IDENTITY }
+    }
+
+    // 2D transform: position, size and rotation around z
+    pub fn from_2d(position: Vec3, size: Vec2, rotation: f32) -> Transform {
+        Transform { translation: position, rotation: Quat::from_rotation_z(rotation), scale: size.extend(1.0) }
+    }
+
+    pub fn from_matrix(matrix: &Mat4) -> Transform {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+        Transform { translation, rotation, scale }
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Transform {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec3) -> Transform {
+        self.scale = scale;
+        self
+    }
+
+    pub fn get_matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    pub fn get_inverse_matrix(&self) -> Mat4 {
+        self.get_matrix().inverse()
+    }
+
+    pub fn get_forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+
+    pub fn get_right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    pub fn get_up(&self) -> Vec3 {
+        self.rotation * Vec3::Y
+    }
+
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.translation + self.rotation * (self.scale * point)
+    }
+
+    // Transform of the child inside this one
+    pub fn combine(&self, child: &Transform) -> Transform {
+        Transform {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale
+        }
+    }
+}
+
+// Axis aligned bounding box
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Aabb {
+        Aabb { min: min.min(max), max: min.max(max) }
+    }
+
+    pub fn from_center(center: Vec3, half_size: Vec3) -> Aabb {
+        Aabb::new(center - half_size, center + half_size)
+    }
+
+    // None without points
+    pub fn from_points(points: &[Vec3]) -> Option<Aabb> {
+        let first = *points.first()?;
+        Some(points.iter().fold(Aabb { min: first, max: first }, |aabb, point| aabb.expanded(*point)))
+    }
+
+    pub fn get_center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn get_size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    // Smallest box with this one and the point
+    pub fn expanded(&self, point: Vec3) -> Aabb {
+        Aabb { min: self.min.min(point), max: self.max.max(point) }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    // Box around the corners of this one after the transform
+    pub fn transformed(&self, matrix: &Mat4) -> Aabb {
+        let corners: Vec<Vec3> = (0..8)
+            .map(|index| Vec3::new(
+                if index & 1 == 0 { self.min.x } else { self.max.x },
+                if index & 2 == 0 { self.min.y } else { self.max.y },
+                if index & 4 == 0 { self.min.z } else { self.max.z }
+            ))
+            .map(|corner| matrix.transform_point3(corner))
+            .collect();
+
+        Aabb::from_points(&corners).unwrap_or(*self)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ray {
+    pub origin: Vec3,
+    // Always normalized
+    pub direction: Vec3
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Ray {
+        Ray { origin, direction: direction.normalize_or_zero() }
+    }
+
+    // Ray under a point of the window, like the mouse position, with the inverse of the camera view projection
+    pub fn from_screen(position: Vec2, viewport_size: Vec2, inverse_view_projection: &Mat4) -> Ray {
+        let ndc = screen_to_ndc(position, viewport_size);
+        let near = inverse_view_projection.project_point3(ndc.extend(-1.0));
+        let far = inverse_view_projection.project_point3(ndc.extend(1.0));
+
+        Ray::new(near, far - near)
+    }
+
+    pub fn get_point(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    // Distance to the box, 0 when the origin is inside
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let inverse = self.direction.recip();
+        let t1 = (aabb.min - self.origin) * inverse;
+        let t2 = (aabb.max - self.origin) * inverse;
+
+        let near = t1.min(t2).max_element();
+        let far = t1.max(t2).min_element();
+
+        (far >= near.max(0.0)).then_some(near.max(0.0))
+    }
+
+    // Distance to the plane of points p where normal.dot(p) == distance
+    pub fn intersect_plane(&self, normal: Vec3, distance: f32) -> Option<f32> {
+        let denominator = normal.dot(self.direction);
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let t = (distance - normal.dot(self.origin)) / denominator;
+        (t >= 0.0).then_some(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, 1e-5), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn screen_to_ndc_corners() {
+        let viewport = Vec2::new(800.0, 600.0);
+        assert_eq!(screen_to_ndc(Vec2::ZERO, viewport), Vec2::new(-1.0, 1.0));
+        assert_eq!(screen_to_ndc(viewport, viewport), Vec2::new(1.0, -1.0));
+        assert_eq!(screen_to_ndc(viewport * 0.5, viewport), Vec2::ZERO);
+    }
+
+    #[test]
+    fn combine_matches_the_matrices() {
+        let parent = Transform::from_translation(Vec3::new(1.0, 2.0, 0.0))
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2))
+            .with_scale(Vec3::splat(2.0));
+        let child = Transform::from_translation(Vec3::new(1.0, 0.0, 0.0));
+
+        let combined = parent.combine(&child);
+        assert_near(combined.translation, Vec3::new(1.0, 4.0, 0.0));
+        assert!(combined.get_matrix().abs_diff_eq(parent.get_matrix() * child.get_matrix(), 1e-5));
+        assert_eq!(Transform::IDENTITY.combine(&child), child);
+    }
+
+    #[test]
+    fn aabb_normalizes_its_corners() {
+        let aabb = Aabb::new(Vec3::ONE, -Vec3::ONE);
+        assert_eq!(aabb.min, -Vec3::ONE);
+        assert!(aabb.contains(Vec3::ZERO));
+        assert!(!aabb.contains(Vec3::new(1.5, 0.0, 0.0)));
+        assert!(aabb.intersects(&Aabb::from_center(Vec3::new(1.5, 0.0, 0.0), Vec3::ONE)));
+        assert!(!aabb.intersects(&Aabb::from_center(Vec3::new(3.5, 0.0, 0.0), Vec3::ONE)));
+        assert_eq!(Aabb::from_points(&[]), None);
+    }
+
+    #[test]
+    fn ray_intersects_aabb() {
+        let aabb = Aabb::from_center(Vec3::ZERO, Vec3::ONE);
+
+        let hit = Ray::new(Vec3::new(-5.0, 0.5, 0.5), Vec3::X).intersect_aabb(&aabb);
+        assert_eq!(hit, Some(4.0));
+
+        // Inside the box the distance is 0
+        assert_eq!(Ray::new(Vec3::ZERO, Vec3::Y).intersect_aabb(&aabb), Some(0.0));
+
+        // Pointing away and passing by
+        assert_eq!(Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::NEG_X).intersect_aabb(&aabb), None);
+        assert_eq!(Ray::new(Vec3::new(-5.0, 2.0, 0.0), Vec3::X).intersect_aabb(&aabb), None);
+    }
+
+    #[test]
+    fn ray_intersects_plane() {
+        let ray = Ray::new(Vec3::new(0.0, 5.0, 0.0), Vec3::NEG_Y);
+        assert_eq!(ray.intersect_plane(Vec3::Y, 1.0), Some(4.0));
+        assert_near(ray.get_point(4.0), Vec3::new(0.0, 1.0, 0.0));
+
+        // Behind the origin and parallel
+        assert_eq!(ray.intersect_plane(Vec3::Y, 6.0), None);
+        assert_eq!(ray.intersect_plane(Vec3::X, 1.0), None);
+    }
+
+    #[test]
+    fn ray_from_screen_center_looks_forward() {
+        let view_projection = perspective(1.0, 1.0, 0.1, 100.0) * look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+        let ray = Ray::from_screen(Vec2::splat(50.0), Vec2::splat(100.0), &view_projection.inverse());
+
+        assert_near(ray.direction, Vec3::NEG_Z);
+        assert!(ray.intersect_aabb(&Aabb::from_center(Vec3::ZERO, Vec3::splat(0.5))).is_some());
+    }
+}
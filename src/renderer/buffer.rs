@@ -0,0 +1,305 @@
+use std::{cell::Cell, mem, rc::Rc};
+
+use bytemuck::Pod;
+use glow::HasContext;
+
+use crate::logger::*;
+
+use super::{RenderCommand, StatsCounter};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShaderDataType {
+    Float, Float2, Float3, Float4,
+    Mat3, Mat4,
+    Int, Int2, Int3, Int4,
+    Bool
+}
+
+impl ShaderDataType {
+    // Size in bytes
+    pub fn size(&self) -> u32 {
+        match self {
+            ShaderDataType::Float  => 4,
+            ShaderDataType::Float2 => 4 * 2,
+            ShaderDataType::Float3 => 4 * 3,
+            ShaderDataType::Float4 => 4 * 4,
+            ShaderDataType::Mat3   => 4 * 3 * 3,
+            ShaderDataType::Mat4   => 4 * 4 * 4,
+            ShaderDataType::Int    => 4,
+            ShaderDataType::Int2   => 4 * 2,
+            ShaderDataType::Int3   => 4 * 3,
+            ShaderDataType::Int4   => 4 * 4,
+            ShaderDataType::Bool   => 1
+        }
+    }
+
+    pub fn component_count(&self) -> u32 {
+        match self {
+            ShaderDataType::Float  => 1,
+            ShaderDataType::Float2 => 2,
+            ShaderDataType::Float3 => 3,
+            ShaderDataType::Float4 => 4,
+            ShaderDataType::Mat3   => 3, // 3 * Float3
+            ShaderDataType::Mat4   => 4, // 4 * Float4
+            ShaderDataType::Int    => 1,
+            ShaderDataType::Int2   => 2,
+            ShaderDataType::Int3   => 3,
+            ShaderDataType::Int4   => 4,
+            ShaderDataType::Bool   => 1
+        }
+    }
+
+    pub(crate) fn gl_base_type(&self) -> u32 {
+        match self {
+            ShaderDataType::Float | ShaderDataType::Float2 | ShaderDataType::Float3 | ShaderDataType::Float4 |
+            ShaderDataType::Mat3 | ShaderDataType::Mat4 => glow::FLOAT,
+            ShaderDataType::Int | ShaderDataType::Int2 | ShaderDataType::Int3 | ShaderDataType::Int4 => glow::INT,
+            ShaderDataType::Bool => glow::UNSIGNED_BYTE
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BufferElement {
+    name: String,
+    data_type: ShaderDataType,
+    normalized: bool,
+    offset: u32
+}
+
+impl BufferElement {
+    pub fn new(data_type: ShaderDataType, name: &str) -> BufferElement {
+        BufferElement { name: name.to_string(), data_type, normalized: false, offset: 0 }
+    }
+
+    // Integer data read as floats in the [0, 1] or [-1, 1] range
+    pub fn normalized(mut self) -> BufferElement {
+        self.normalized = true;
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_data_type(&self) -> ShaderDataType {
+        self.data_type
+    }
+
+    pub fn is_normalized(&self) -> bool {
+        self.normalized
+    }
+
+    pub fn get_offset(&self) -> u32 {
+        self.offset
+    }
+}
+
+// Attributes of the vertices in a buffer, in the order they are stored
+#[derive(Clone, Default, Debug)]
+pub struct BufferLayout {
+    elements: Vec<BufferElement>,
+    stride: u32
+}
+
+impl BufferLayout {
+    pub fn new(mut elements: Vec<BufferElement>) -> BufferLayout {
+        let mut offset = 0;
+
+        for element in elements.iter_mut() {
+            element.offset = offset;
+            offset += element.data_type.size();
+        }
+
+        BufferLayout { elements, stride: offset }
+    }
+
+    pub fn get_elements(&self) -> &[BufferElement] {
+        &self.elements
+    }
+
+    pub fn get_stride(&self) -> u32 {
+        self.stride
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BufferUsage {
+    // Written once, drawn many times
+    Static,
+    // Rewritten from time to time
+    Dynamic,
+    // Rewritten every frame
+    Stream
+}
+
+impl BufferUsage {
+    fn to_gl(self) -> u32 {
+        match self {
+            BufferUsage::Static => glow::STATIC_DRAW,
+            BufferUsage::Dynamic => glow::DYNAMIC_DRAW,
+            BufferUsage::Stream => glow::STREAM_DRAW,
+        }
+    }
+}
+
+// Bytes of a slice of plain data like f32, u32 or #[repr(C)] vertex structs without padding
+pub(crate) fn as_bytes<T: Pod>(data: &[T]) -> &[u8] {
+    bytemuck::cast_slice(data)
+}
+
+pub struct VertexBuffer {
+    gl: Rc<glow::Context>,
+    stats: Rc<StatsCounter>,
+    buffer: glow::Buffer,
+    size: usize,
+    usage: BufferUsage,
+    layout: BufferLayout
+}
+
+impl VertexBuffer {
+    pub fn new<T: Pod>(render_command: &RenderCommand, vertices: &[T], usage: BufferUsage) -> VertexBuffer {
+        let buffer = Self::with_size(render_command, mem::size_of_val(vertices), usage);
+        buffer.set_data(0, vertices);
+        buffer
+    }
+
+    // Empty buffer that is filled later with set_data
+    pub fn with_size(render_command: &RenderCommand, size: usize, usage: BufferUsage) -> VertexBuffer {
+        let gl = render_command.gl().clone();
+
+        let buffer = unsafe {
+            let buffer = gl.create_buffer().expect("Failed to create vertex buffer");
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(buffer));
+            gl.buffer_data_size(glow::ARRAY_BUFFER, size as i32, usage.to_gl());
+            buffer
+        };
+
+        VertexBuffer { gl, stats: render_command.get_stats_counter().clone(), buffer, size, usage, layout: BufferLayout::default() }
+    }
+
+    // Writes the vertices starting at the offset in bytes
+    pub fn set_data<T: Pod>(&self, offset: usize, vertices: &[T]) {
+        let bytes = as_bytes(vertices);
+        if offset + bytes.len() > self.size {
+            hds_core_error!("Vertex buffer write of {} bytes at {} overflows its {} bytes", bytes.len(), offset, self.size);
+            return;
+        }
+
+        unsafe {
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.buffer));
+            self.gl.buffer_sub_data_u8_slice(glow::ARRAY_BUFFER, offset as i32, bytes);
+        }
+
+        self.stats.add_buffer_upload(bytes.len());
+    }
+
+    pub fn bind(&self) {
+        unsafe { self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.buffer)) };
+    }
+
+    pub fn unbind(&self) {
+        unsafe { self.gl.bind_buffer(glow::ARRAY_BUFFER, None) };
+    }
+
+    pub fn set_layout(&mut self, layout: BufferLayout) {
+        self.layout = layout;
+    }
+
+    pub fn get_layout(&self) -> &BufferLayout {
+        &self.layout
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get_usage(&self) -> BufferUsage {
+        self.usage
+    }
+}
+
+impl Drop for VertexBuffer {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_buffer(self.buffer) };
+    }
+}
+
+// Buffer of u32 indices
+pub struct IndexBuffer {
+    gl: Rc<glow::Context>,
+    stats: Rc<StatsCounter>,
+    buffer: glow::Buffer,
+    capacity: u32,
+    count: Cell<u32>,
+    usage: BufferUsage
+}
+
+impl IndexBuffer {
+    pub fn new(render_command: &RenderCommand, indices: &[u32], usage: BufferUsage) -> IndexBuffer {
+        let buffer = Self::with_capacity(render_command, indices.len() as u32, usage);
+        buffer.set_data(0, indices);
+        buffer.count.set(indices.len() as u32);
+        buffer
+    }
+
+    pub fn with_capacity(render_command: &RenderCommand, capacity: u32, usage: BufferUsage) -> IndexBuffer {
+        let gl = render_command.gl().clone();
+
+        // The element array binding belongs to the bound vertex array, so the data goes through
+        // the copy target instead, which doesn't change the index buffer of any vertex array
+        let buffer = unsafe {
+            let buffer = gl.create_buffer().expect("Failed to create index buffer");
+            gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(buffer));
+            gl.buffer_data_size(glow::COPY_WRITE_BUFFER, (capacity as usize * mem::size_of::<u32>()) as i32, usage.to_gl());
+            buffer
+        };
+
+        IndexBuffer { gl, stats: render_command.get_stats_counter().clone(), buffer, capacity, count: Cell::new(0), usage }
+    }
+
+    // Writes the indices starting at the offset, counted in indices. A write at 0 replaces
+    // the indices and sets the count, the others grow it to cover them
+    pub fn set_data(&self, offset: u32, indices: &[u32]) {
+        let end = offset + indices.len() as u32;
+        if end > self.capacity {
+            hds_core_error!("Index buffer write of {} indices at {} overflows its {} indices", indices.len(), offset, self.capacity);
+            return;
+        }
+
+        unsafe {
+            self.gl.bind_buffer(glow::COPY_WRITE_BUFFER, Some(self.buffer));
+            self.gl.buffer_sub_data_u8_slice(glow::COPY_WRITE_BUFFER, (offset as usize * mem::size_of::<u32>()) as i32, as_bytes(indices));
+        }
+
+        self.stats.add_buffer_upload(mem::size_of_val(indices));
+        self.count.set(if offset == 0 { end } else { self.count.get().max(end) });
+    }
+
+    // Makes it the index buffer of the bound vertex array, see VertexArray::set_index_buffer
+    pub fn bind(&self) {
+        unsafe { self.gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.buffer)) };
+    }
+
+    pub fn get_count(&self) -> u32 {
+        self.count.get()
+    }
+
+    pub fn get_capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub fn get_usage(&self) -> BufferUsage {
+        self.usage
+    }
+}
+
+impl Drop for IndexBuffer {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_buffer(self.buffer) };
+    }
+}
@@ -0,0 +1,166 @@
+use crate::math::{self, Mat4, Quat, Vec3};
+
+// Anything that can be used to begin a scene in the renderers
+pub trait Camera {
+    fn get_view_projection(&self) -> Mat4;
+    fn get_position(&self) -> Vec3;
+}
+
+pub struct OrthographicCamera {
+    projection: Mat4,
+    view: Mat4,
+    view_projection: Mat4,
+    position: Vec3,
+    // Radians around the z axis
+    rotation: f32
+}
+
+impl OrthographicCamera {
+    pub fn new(left: f32, right: f32, bottom: f32, top: f32) -> OrthographicCamera {
+        let projection = math::orthographic(left, right, bottom, top, -1.0, 1.0);
+        OrthographicCamera { projection, view: Mat4::IDENTITY, view_projection: projection, position: Vec3::ZERO, rotation: 0.0 }
+    }
+
+    pub fn set_projection(&mut self, left: f32, right: f32, bottom: f32, top: f32) {
+        self.projection = math::orthographic(left, right, bottom, top, -1.0, 1.0);
+        self.view_projection = self.projection * self.view;
+    }
+
+    pub fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+        self.update_view();
+    }
+
+    pub fn get_position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+        self.update_view();
+    }
+
+    pub fn get_rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn get_projection(&self) -> &Mat4 {
+        &self.projection
+    }
+
+    pub fn get_view(&self) -> &Mat4 {
+        &self.view
+    }
+
+    // The view is the inverse of the camera transform
+    fn update_view(&mut self) {
+        self.view = Mat4::from_rotation_translation(Quat::from_rotation_z(self.rotation), self.position).inverse();
+        self.view_projection = self.projection * self.view;
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn get_view_projection(&self) -> Mat4 {
+        self.view_projection
+    }
+
+    fn get_position(&self) -> Vec3 {
+        self.position
+    }
+}
+
+pub struct PerspectiveCamera {
+    // Vertical field of view in radians
+    fov: f32,
+    aspect_ratio: f32,
+    near: f32,
+    far: f32,
+    position: Vec3,
+    // Radians, a yaw of 0 looks at -z and a positive pitch looks up
+    yaw: f32,
+    pitch: f32
+}
+
+impl PerspectiveCamera {
+    pub fn new(fov: f32, aspect_ratio: f32, near: f32, far: f32) -> PerspectiveCamera {
+        PerspectiveCamera { fov, aspect_ratio, near, far, position: Vec3::ZERO, yaw: 0.0, pitch: 0.0 }
+    }
+
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+    }
+
+    pub fn get_aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov;
+    }
+
+    pub fn get_fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        self.near = near;
+        self.far = far;
+    }
+
+    pub fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+    }
+
+    pub fn get_position(&self) -> Vec3 {
+        self.position
+    }
+
+    // The pitch is kept short of straight up or down, where the view flips
+    pub fn set_rotation(&mut self, yaw: f32, pitch: f32) {
+        let limit = std::f32::consts::FRAC_PI_2 - 0.001;
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-limit, limit);
+    }
+
+    pub fn get_yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    pub fn get_pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    pub fn get_orientation(&self) -> Quat {
+        Quat::from_rotation_y(-self.yaw) * Quat::from_rotation_x(self.pitch)
+    }
+
+    pub fn get_forward(&self) -> Vec3 {
+        self.get_orientation() * Vec3::NEG_Z
+    }
+
+    pub fn get_right(&self) -> Vec3 {
+        self.get_orientation() * Vec3::X
+    }
+
+    pub fn get_up(&self) -> Vec3 {
+        self.get_orientation() * Vec3::Y
+    }
+
+    pub fn get_projection(&self) -> Mat4 {
+        math::perspective(self.fov, self.aspect_ratio, self.near, self.far)
+    }
+
+    pub fn get_view(&self) -> Mat4 {
+        Mat4::from_rotation_translation(self.get_orientation(), self.position).inverse()
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    fn get_view_projection(&self) -> Mat4 {
+        self.get_projection() * self.get_view()
+    }
+
+    fn get_position(&self) -> Vec3 {
+        self.position
+    }
+}
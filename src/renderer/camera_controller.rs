@@ -0,0 +1,226 @@
+use std::time::Instant;
+
+use winit::keyboard::KeyCode;
+
+use crate::{events::{Event, EventType}, math::{Vec2, Vec3}};
+
+use super::{OrthographicCamera, PerspectiveCamera};
+
+// Keys held down, the events only tell when they change
+#[derive(Default)]
+struct HeldKeys {
+    keys: Vec<i32>
+}
+
+impl HeldKeys {
+    fn on_event(&mut self, event: &Event) {
+        match event.get_event_type() {
+            EventType::KeyPressed { keycode, .. } if !self.keys.contains(&keycode) => self.keys.push(keycode),
+            EventType::KeyReleased { keycode } => self.keys.retain(|key| *key != keycode),
+            _ => (),
+        }
+    }
+
+    fn is_held(&self, key: KeyCode) -> bool {
+        self.keys.contains(&(key as i32))
+    }
+
+    // -1, 0 or 1 depending on which of the two keys is held
+    fn axis(&self, negative: KeyCode, positive: KeyCode) -> f32 {
+        (self.is_held(positive) as i32 - self.is_held(negative) as i32) as f32
+    }
+}
+
+// 2D camera moved with WASD, rotated with Q and E and zoomed with the mouse wheel
+pub struct OrthographicCameraController {
+    camera: OrthographicCamera,
+    aspect_ratio: f32,
+    zoom_level: f32,
+    rotation_enabled: bool,
+    // World units per second at zoom level 1, the speed follows the zoom
+    translation_speed: f32,
+    // Radians per second
+    rotation_speed: f32,
+    keys: HeldKeys,
+    last_update: Instant
+}
+
+impl OrthographicCameraController {
+    pub fn new(aspect_ratio: f32, rotation_enabled: bool) -> OrthographicCameraController {
+        let zoom_level = 1.0;
+
+        OrthographicCameraController {
+            camera: OrthographicCamera::new(-aspect_ratio * zoom_level, aspect_ratio * zoom_level, -zoom_level, zoom_level),
+            aspect_ratio,
+            zoom_level,
+            rotation_enabled,
+            translation_speed: 2.0,
+            rotation_speed: std::f32::consts::PI,
+            keys: HeldKeys::default(),
+            last_update: Instant::now()
+        }
+    }
+
+    // Moves the camera with the keys held since the last update
+    pub fn on_update(&mut self) {
+        let now = Instant::now();
+        let delta = (now - self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        let rotation = self.camera.get_rotation();
+        let speed = self.translation_speed * self.zoom_level * delta;
+
+        // The movement follows the rotation of the camera
+        let movement = Vec2::new(self.keys.axis(KeyCode::KeyA, KeyCode::KeyD), self.keys.axis(KeyCode::KeyS, KeyCode::KeyW));
+        let movement = Vec2::from_angle(rotation).rotate(movement) * speed;
+        self.camera.set_position(self.camera.get_position() + movement.extend(0.0));
+
+        if self.rotation_enabled {
+            let turn = self.keys.axis(KeyCode::KeyE, KeyCode::KeyQ) * self.rotation_speed * delta;
+            if turn != 0.0 {
+                self.camera.set_rotation(rotation + turn);
+            }
+        }
+    }
+
+    // Returns true for the events used by the controller
+    pub fn on_event(&mut self, event: &Event) -> bool {
+        match event.get_event_type() {
+            EventType::MouseScrolled { offset } => {
+                self.set_zoom_level(self.zoom_level - offset.y * 0.25);
+                true
+            },
+            EventType::WindowResize { width, height } if width > 0 && height > 0 => {
+                self.set_aspect_ratio(width as f32 / height as f32);
+                false
+            },
+            // Other layers may also use the keys
+            _ => {
+                self.keys.on_event(event);
+                false
+            },
+        }
+    }
+
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+        self.update_projection();
+    }
+
+    // Half of the visible height in world units
+    pub fn set_zoom_level(&mut self, zoom_level: f32) {
+        self.zoom_level = zoom_level.max(0.25);
+        self.update_projection();
+    }
+
+    pub fn get_zoom_level(&self) -> f32 {
+        self.zoom_level
+    }
+
+    pub fn set_translation_speed(&mut self, speed: f32) {
+        self.translation_speed = speed;
+    }
+
+    pub fn get_camera(&self) -> &OrthographicCamera {
+        &self.camera
+    }
+
+    pub fn get_camera_mut(&mut self) -> &mut OrthographicCamera {
+        &mut self.camera
+    }
+
+    fn update_projection(&mut self) {
+        let (width, height) = (self.aspect_ratio * self.zoom_level, self.zoom_level);
+        self.camera.set_projection(-width, width, -height, height);
+    }
+}
+
+// 3D camera moved with WASD, Space and Left Shift, it looks around while the right mouse button is held
+pub struct FlyCameraController {
+    camera: PerspectiveCamera,
+    // World units per second
+    speed: f32,
+    // Radians per pixel
+    sensitivity: f32,
+    keys: HeldKeys,
+    looking: bool,
+    last_mouse: Option<Vec2>,
+    last_update: Instant
+}
+
+impl FlyCameraController {
+    pub fn new(camera: PerspectiveCamera) -> FlyCameraController {
+        FlyCameraController {
+            camera,
+            speed: 5.0,
+            sensitivity: 0.003,
+            keys: HeldKeys::default(),
+            looking: false,
+            last_mouse: None,
+            last_update: Instant::now()
+        }
+    }
+
+    pub fn on_update(&mut self) {
+        let now = Instant::now();
+        let delta = (now - self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        // Forward and right follow the camera, up is always the world up
+        let movement = self.camera.get_forward() * self.keys.axis(KeyCode::KeyS, KeyCode::KeyW)
+            + self.camera.get_right() * self.keys.axis(KeyCode::KeyA, KeyCode::KeyD)
+            + Vec3::Y * self.keys.axis(KeyCode::ShiftLeft, KeyCode::Space);
+
+        self.camera.set_position(self.camera.get_position() + movement * self.speed * delta);
+    }
+
+    // Returns true for the events used by the controller
+    pub fn on_event(&mut self, event: &Event) -> bool {
+        match event.get_event_type() {
+            // Right button
+            EventType::MouseButtonPressed { button: 1 } => {
+                self.looking = true;
+                true
+            },
+            EventType::MouseButtonReleased { button: 1 } => {
+                self.looking = false;
+                self.last_mouse = None;
+                true
+            },
+            EventType::MouseMoved { position } => {
+                if let (true, Some(last_mouse)) = (self.looking, self.last_mouse) {
+                    let delta = (position - last_mouse) * self.sensitivity;
+                    self.camera.set_rotation(self.camera.get_yaw() + delta.x, self.camera.get_pitch() - delta.y);
+                }
+
+                self.last_mouse = self.looking.then_some(position);
+                self.looking
+            },
+            EventType::WindowResize { width, height } if width > 0 && height > 0 => {
+                self.camera.set_aspect_ratio(width as f32 / height as f32);
+                false
+            },
+            // Other layers may also use the keys
+            _ => {
+                self.keys.on_event(event);
+                false
+            },
+        }
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.sensitivity = sensitivity;
+    }
+
+    pub fn get_camera(&self) -> &PerspectiveCamera {
+        &self.camera
+    }
+
+    pub fn get_camera_mut(&mut self) -> &mut PerspectiveCamera {
+        &mut self.camera
+    }
+}
@@ -0,0 +1,66 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use glow::HasContext;
+use image::RgbaImage;
+use time::{format_description::FormatItem, OffsetDateTime};
+
+use crate::logger::format::local_offset;
+
+use super::RenderCommand;
+
+const SCREENSHOT_NAME_FORMAT: &[FormatItem] =
+    time::macros::format_description!("[year][month][day]_[hour][minute][second]");
+
+// Colors of the window back buffer, read before the buffers are swapped because
+// the back buffer is undefined after the swap
+pub fn capture_window(render_command: &RenderCommand, width: u32, height: u32) -> RgbaImage {
+    let mut image = read_rgba(render_command.gl(), None, glow::BACK, width, height);
+
+    // The alpha of the window is not meaningful, the screenshots are opaque
+    image.pixels_mut().for_each(|pixel| pixel[3] = 255);
+    image
+}
+
+// Reads the pixels of the framebuffer, or of the window without one, with the first row at the top
+pub(crate) fn read_rgba(gl: &glow::Context, framebuffer: Option<glow::Framebuffer>, read_buffer: u32, width: u32, height: u32) -> RgbaImage {
+    let row_size = width as usize * 4;
+    let mut pixels = vec![0u8; row_size * height as usize];
+
+    unsafe {
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, framebuffer);
+        gl.read_buffer(read_buffer);
+        // Rows of any width are packed without padding
+        gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+        gl.read_pixels(0, 0, width as i32, height as i32, glow::RGBA, glow::UNSIGNED_BYTE, glow::PixelPackData::Slice(&mut pixels));
+        gl.pixel_store_i32(glow::PACK_ALIGNMENT, 4);
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+    }
+
+    // GL rows start at the bottom, image rows at the top
+    let flipped: Vec<u8> = pixels.chunks_exact(row_size).rev().flatten().copied().collect();
+    RgbaImage::from_raw(width, height, flipped).expect("The pixels match the image size")
+}
+
+pub fn save_png(image: &RgbaImage, path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed to create {}: {}", dir.display(), err))?;
+    }
+
+    image.save_with_format(path, image::ImageFormat::Png)
+        .map_err(|err| format!("Failed to save {}: {}", path.display(), err))
+}
+
+// screenshot_YYYYMMDD_HHMMSS.png in the directory, with a number when several are taken in the same second
+pub fn screenshot_path(dir: &Path) -> PathBuf {
+    let now = OffsetDateTime::now_utc().to_offset(local_offset());
+    let name = format!("screenshot_{}", now.format(SCREENSHOT_NAME_FORMAT).unwrap_or_else(|_| "unknown".to_string()));
+
+    let mut path = dir.join(format!("{}.png", name));
+    let mut index = 1;
+    while path.exists() {
+        path = dir.join(format!("{}_{}.png", name, index));
+        index += 1;
+    }
+
+    path
+}
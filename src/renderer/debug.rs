@@ -0,0 +1,113 @@
+use std::panic::Location;
+
+use glow::HasContext;
+use log::Level;
+
+use crate::logger::*;
+
+// Target of the driver messages, so they can be filtered with log.level GL <level>
+const LOG_TARGET: &str = "GL";
+
+// Routes the KHR_debug messages of the driver into the logger, only debug contexts send them.
+// Returns false when the context can't send debug messages
+pub fn enable_debug_output(gl: &mut glow::Context) -> bool {
+    let flags = unsafe { gl.get_parameter_i32(glow::CONTEXT_FLAGS) } as u32;
+    if flags & glow::CONTEXT_FLAG_DEBUG_BIT == 0 {
+        return false;
+    }
+
+    let extensions = gl.supported_extensions();
+    let version = gl.version();
+    let supported = (version.major, version.minor) >= (4, 3)
+        || extensions.contains("GL_KHR_debug")
+        || extensions.contains("GL_ARB_debug_output");
+
+    if !supported {
+        hds_core_warn!("Debug context without KHR_debug or ARB_debug_output, the driver messages are not logged");
+        return false;
+    }
+
+    unsafe {
+        gl.enable(glow::DEBUG_OUTPUT);
+        // The messages are sent during the call that caused them, so the backtraces point at it
+        gl.enable(glow::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl.debug_message_control(glow::DONT_CARE, glow::DONT_CARE, glow::DONT_CARE, &[], true);
+        gl.debug_message_callback(log_message);
+    }
+
+    hds_core_info!("GL debug output enabled");
+    true
+}
+
+fn log_message(source: u32, message_type: u32, id: u32, severity: u32, message: &str) {
+    let level = match severity {
+        glow::DEBUG_SEVERITY_HIGH => Level::Error,
+        glow::DEBUG_SEVERITY_MEDIUM => Level::Warn,
+        glow::DEBUG_SEVERITY_LOW => Level::Info,
+        _ => Level::Trace,
+    };
+
+    // Errors reported by the driver are errors whatever their severity
+    let level = if message_type == glow::DEBUG_TYPE_ERROR { Level::Error } else { level };
+
+    log::log!(target: LOG_TARGET, level, "{} {} {}: {}", source_name(source), type_name(message_type), id, message.trim_end());
+}
+
+fn source_name(source: u32) -> &'static str {
+    match source {
+        glow::DEBUG_SOURCE_API => "API",
+        glow::DEBUG_SOURCE_WINDOW_SYSTEM => "Window system",
+        glow::DEBUG_SOURCE_SHADER_COMPILER => "Shader compiler",
+        glow::DEBUG_SOURCE_THIRD_PARTY => "Third party",
+        glow::DEBUG_SOURCE_APPLICATION => "Application",
+        _ => "Other",
+    }
+}
+
+fn type_name(message_type: u32) -> &'static str {
+    match message_type {
+        glow::DEBUG_TYPE_ERROR => "error",
+        glow::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+        glow::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+        glow::DEBUG_TYPE_PORTABILITY => "portability",
+        glow::DEBUG_TYPE_PERFORMANCE => "performance",
+        glow::DEBUG_TYPE_MARKER => "marker",
+        glow::DEBUG_TYPE_PUSH_GROUP => "push group",
+        glow::DEBUG_TYPE_POP_GROUP => "pop group",
+        _ => "message",
+    }
+}
+
+pub fn error_name(error: u32) -> &'static str {
+    match error {
+        glow::INVALID_ENUM => "GL_INVALID_ENUM",
+        glow::INVALID_VALUE => "GL_INVALID_VALUE",
+        glow::INVALID_OPERATION => "GL_INVALID_OPERATION",
+        glow::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+        glow::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
+        glow::STACK_UNDERFLOW => "GL_STACK_UNDERFLOW",
+        glow::STACK_OVERFLOW => "GL_STACK_OVERFLOW",
+        _ => "Unknown GL error",
+    }
+}
+
+// Logs the errors raised since the last check with the call and the place in the code that made it,
+// returns the number of errors
+#[track_caller]
+pub fn check_errors(gl: &glow::Context, call: &str) -> usize {
+    let location = Location::caller();
+    let mut count = 0;
+
+    // Every error flag that is set is returned once, with a limit in case the context was lost
+    while count < 16 {
+        let error = unsafe { gl.get_error() };
+        if error == glow::NO_ERROR {
+            break;
+        }
+
+        hds_core_error!("{} (0x{:04X}) in {} at {}", error_name(error), error, call, location);
+        count += 1;
+    }
+
+    count
+}
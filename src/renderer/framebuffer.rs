@@ -0,0 +1,381 @@
+use std::{cell::Cell, num::NonZeroU32};
+
+use glow::HasContext;
+use image::RgbaImage;
+use imgui::Ui;
+
+use crate::logger::*;
+
+use super::{capture, RenderCommand, RendererAPI};
+
+// Bigger sizes are rejected, they usually come from a broken resize
+const MAX_SIZE: u32 = 8192;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorFormat {
+    Rgba8,
+    // HDR color
+    Rgba16F,
+    // Integer values like entity ids for mouse picking
+    R32I
+}
+
+impl ColorFormat {
+    // Internal format, format and type of the attachment
+    fn to_gl(self) -> (u32, u32, u32) {
+        match self {
+            ColorFormat::Rgba8 => (glow::RGBA8, glow::RGBA, glow::UNSIGNED_BYTE),
+            ColorFormat::Rgba16F => (glow::RGBA16F, glow::RGBA, glow::FLOAT),
+            ColorFormat::R32I => (glow::R32I, glow::RED_INTEGER, glow::INT),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DepthFormat {
+    Depth24Stencil8,
+    Depth32F
+}
+
+impl DepthFormat {
+    // Internal format and attachment point
+    fn to_gl(self) -> (u32, u32) {
+        match self {
+            DepthFormat::Depth24Stencil8 => (glow::DEPTH24_STENCIL8, glow::DEPTH_STENCIL_ATTACHMENT),
+            DepthFormat::Depth32F => (glow::DEPTH_COMPONENT32F, glow::DEPTH_ATTACHMENT),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FramebufferSpec {
+    pub width: u32,
+    pub height: u32,
+    pub color_attachments: Vec<ColorFormat>,
+    pub depth_attachment: Option<DepthFormat>,
+    // More than 1 renders with MSAA, resolve copies the result to the textures
+    pub samples: u32,
+    // Takes the size of the window when it is bound
+    pub follow_window: bool
+}
+
+impl Default for FramebufferSpec {
+    fn default() -> FramebufferSpec {
+        FramebufferSpec {
+            width: 1280,
+            height: 720,
+            color_attachments: vec![ColorFormat::Rgba8],
+            depth_attachment: Some(DepthFormat::Depth24Stencil8),
+            samples: 1,
+            follow_window: true
+        }
+    }
+}
+
+// GL objects of one size of the framebuffer, created again on resize
+struct Attachments {
+    framebuffer: glow::Framebuffer,
+    // Multisampled color and depth storage
+    renderbuffers: Vec<glow::Renderbuffer>,
+    // Textures with the final colors, in the resolve framebuffer when there is MSAA
+    resolve_framebuffer: Option<glow::Framebuffer>,
+    textures: Vec<glow::Texture>
+}
+
+// Framebuffer and viewport to restore on unbind, no framebuffer is the window
+type PreviousTarget = (Option<glow::Framebuffer>, (i32, i32, u32, u32));
+
+pub struct Framebuffer {
+    render_command: RenderCommand,
+    spec: FramebufferSpec,
+    attachments: Attachments,
+    previous_target: Cell<Option<PreviousTarget>>
+}
+
+impl Framebuffer {
+    pub fn new(render_command: &RenderCommand, spec: FramebufferSpec) -> Result<Framebuffer, String> {
+        let attachments = create_attachments(render_command.gl(), &spec)?;
+        Ok(Framebuffer { render_command: render_command.clone(), spec, attachments, previous_target: Cell::new(None) })
+    }
+
+    // Draws into the framebuffer with a viewport of its size
+    pub fn bind(&mut self) {
+        // The render command knows the window size, so the owners don't have to forward the resize events
+        if self.spec.follow_window {
+            let (width, height) = self.render_command.get_window_size();
+            if width > 0 && height > 0 {
+                self.resize(width, height);
+            }
+        }
+
+        let gl = self.render_command.gl();
+
+        if self.previous_target.get().is_none() {
+            let binding = unsafe { gl.get_parameter_i32(glow::DRAW_FRAMEBUFFER_BINDING) };
+            let framebuffer = NonZeroU32::new(binding as u32).map(glow::NativeFramebuffer);
+            self.previous_target.set(Some((framebuffer, self.render_command.get_viewport())));
+        }
+
+        unsafe { gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.attachments.framebuffer)) };
+        self.render_command.get_stats_counter().add_state_change();
+        self.render_command.set_viewport(0, 0, self.spec.width, self.spec.height);
+    }
+
+    // Draws into the previous target again, the window or another framebuffer,
+    // MSAA framebuffers are resolved
+    pub fn unbind(&self) {
+        self.resolve();
+
+        let (framebuffer, (x, y, width, height)) = self.previous_target.take().unwrap_or((None, self.render_command.get_viewport()));
+        unsafe { self.render_command.gl().bind_framebuffer(glow::FRAMEBUFFER, framebuffer) };
+        self.render_command.get_stats_counter().add_state_change();
+        self.render_command.set_viewport(x, y, width, height);
+    }
+
+    // Copies the multisampled colors to the textures, nothing to do without MSAA
+    pub fn resolve(&self) {
+        let resolve_framebuffer = match self.attachments.resolve_framebuffer {
+            Some(framebuffer) => framebuffer,
+            None => return,
+        };
+
+        let gl = self.render_command.gl();
+        let (width, height) = (self.spec.width as i32, self.spec.height as i32);
+
+        unsafe {
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.attachments.framebuffer));
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(resolve_framebuffer));
+
+            // A blit writes every draw buffer, so they are selected one by one
+            for index in 0..self.spec.color_attachments.len() {
+                let mut draw_buffers = vec![glow::NONE; self.spec.color_attachments.len()];
+                draw_buffers[index] = glow::COLOR_ATTACHMENT0 + index as u32;
+
+                gl.read_buffer(glow::COLOR_ATTACHMENT0 + index as u32);
+                gl.draw_buffers(&draw_buffers);
+                gl.blit_framebuffer(0, 0, width, height, 0, 0, width, height, glow::COLOR_BUFFER_BIT, glow::NEAREST);
+            }
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.attachments.framebuffer));
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 || width > MAX_SIZE || height > MAX_SIZE {
+            hds_core_warn!("Invalid framebuffer size {}x{}", width, height);
+            return;
+        }
+
+        if (width, height) == (self.spec.width, self.spec.height) {
+            return;
+        }
+
+        self.spec.width = width;
+        self.spec.height = height;
+
+        match create_attachments(self.render_command.gl(), &self.spec) {
+            Ok(attachments) => {
+                delete_attachments(self.render_command.gl(), &self.attachments);
+                self.attachments = attachments;
+            },
+            Err(err) => hds_core_error!("Failed to resize framebuffer: {}", err),
+        }
+    }
+
+    // Sets every pixel of an integer attachment, the framebuffer must be bound
+    pub fn clear_attachment(&self, index: usize, value: i32) {
+        unsafe { self.render_command.gl().clear_buffer_i32_slice(glow::COLOR, index as u32, &[value; 4]) };
+    }
+
+    // Value of a R32I attachment, with the origin at the bottom left corner
+    pub fn read_pixel(&self, index: usize, x: u32, y: u32) -> Option<i32> {
+        if self.spec.color_attachments.get(index) != Some(&ColorFormat::R32I) || x >= self.spec.width || y >= self.spec.height {
+            return None;
+        }
+
+        let gl = self.render_command.gl();
+        let framebuffer = self.attachments.resolve_framebuffer.unwrap_or(self.attachments.framebuffer);
+        let mut pixel = [0u8; 4];
+
+        unsafe {
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(framebuffer));
+            gl.read_buffer(glow::COLOR_ATTACHMENT0 + index as u32);
+            gl.read_pixels(x as i32, y as i32, 1, 1, glow::RED_INTEGER, glow::INT, glow::PixelPackData::Slice(&mut pixel));
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+
+        Some(i32::from_ne_bytes(pixel))
+    }
+
+    // Colors of an attachment with the first row at the top, integer attachments can't be captured
+    pub fn capture(&self, index: usize) -> Result<RgbaImage, String> {
+        match self.spec.color_attachments.get(index) {
+            Some(ColorFormat::R32I) => return Err(format!("Attachment {} has integer values", index)),
+            Some(_) => (),
+            None => return Err(format!("There is no attachment {}", index)),
+        }
+
+        let framebuffer = self.attachments.resolve_framebuffer.unwrap_or(self.attachments.framebuffer);
+        Ok(capture::read_rgba(self.render_command.gl(), Some(framebuffer), glow::COLOR_ATTACHMENT0 + index as u32, self.spec.width, self.spec.height))
+    }
+
+    pub fn get_spec(&self) -> &FramebufferSpec {
+        &self.spec
+    }
+
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.spec.width, self.spec.height)
+    }
+
+    pub(crate) fn get_color_texture(&self, index: usize) -> Option<glow::Texture> {
+        self.attachments.textures.get(index).copied()
+    }
+
+    pub fn get_imgui_id(&self, index: usize) -> Option<imgui::TextureId> {
+        self.get_color_texture(index).map(|texture| imgui::TextureId::new(texture.0.get() as usize))
+    }
+
+    // Draws a color attachment in an imgui window, framebuffers that don't follow
+    // the window take the size of the window content
+    pub fn show_viewport(&mut self, ui: &Ui, title: &str, index: usize) {
+        ui.window(title).build(|| {
+            let available = ui.content_region_avail();
+            let (width, height) = (available[0].max(1.0) as u32, available[1].max(1.0) as u32);
+
+            // Before queuing the image, a resize deletes the texture it would point to
+            if !self.spec.follow_window {
+                self.resize(width, height);
+            }
+
+            if let Some(texture) = self.get_imgui_id(index) {
+                // GL textures start at the bottom, imgui images at the top
+                imgui::Image::new(texture, [width as f32, height as f32])
+                    .uv0([0.0, 1.0])
+                    .uv1([1.0, 0.0])
+                    .build(ui);
+            }
+        });
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        delete_attachments(self.render_command.gl(), &self.attachments);
+    }
+}
+
+fn create_attachments(gl: &glow::Context, spec: &FramebufferSpec) -> Result<Attachments, String> {
+    let (width, height) = (spec.width as i32, spec.height as i32);
+    let multisampled = spec.samples > 1;
+
+    unsafe {
+        let framebuffer = gl.create_framebuffer()?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+
+        let mut attachments = Attachments { framebuffer, renderbuffers: vec![], resolve_framebuffer: None, textures: vec![] };
+
+        if multisampled {
+            for (index, format) in spec.color_attachments.iter().enumerate() {
+                let renderbuffer = gl.create_renderbuffer()?;
+                gl.bind_renderbuffer(glow::RENDERBUFFER, Some(renderbuffer));
+                gl.renderbuffer_storage_multisample(glow::RENDERBUFFER, spec.samples as i32, format.to_gl().0, width, height);
+                gl.framebuffer_renderbuffer(glow::FRAMEBUFFER, glow::COLOR_ATTACHMENT0 + index as u32, glow::RENDERBUFFER, Some(renderbuffer));
+                attachments.renderbuffers.push(renderbuffer);
+            }
+        }
+        else {
+            attachments.textures = create_color_textures(gl, spec)?;
+        }
+
+        if let Some(depth) = spec.depth_attachment {
+            let (internal_format, attachment) = depth.to_gl();
+            let renderbuffer = gl.create_renderbuffer()?;
+            gl.bind_renderbuffer(glow::RENDERBUFFER, Some(renderbuffer));
+
+            if multisampled {
+                gl.renderbuffer_storage_multisample(glow::RENDERBUFFER, spec.samples as i32, internal_format, width, height);
+            }
+            else {
+                gl.renderbuffer_storage(glow::RENDERBUFFER, internal_format, width, height);
+            }
+
+            gl.framebuffer_renderbuffer(glow::FRAMEBUFFER, attachment, glow::RENDERBUFFER, Some(renderbuffer));
+            attachments.renderbuffers.push(renderbuffer);
+        }
+
+        let draw_buffers: Vec<u32> = (0..spec.color_attachments.len() as u32).map(|index| glow::COLOR_ATTACHMENT0 + index).collect();
+        gl.draw_buffers(if draw_buffers.is_empty() { &[glow::NONE] } else { &draw_buffers });
+
+        let mut status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        let mut name = "Framebuffer";
+
+        // Without color attachments there is nothing to resolve
+        if multisampled && !spec.color_attachments.is_empty() && status == glow::FRAMEBUFFER_COMPLETE {
+            let resolve_framebuffer = gl.create_framebuffer()?;
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(resolve_framebuffer));
+            attachments.resolve_framebuffer = Some(resolve_framebuffer);
+            attachments.textures = create_color_textures(gl, spec)?;
+
+            status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            name = "MSAA resolve framebuffer";
+        }
+
+        gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            delete_attachments(gl, &attachments);
+            return Err(format!("{} is incomplete, status 0x{:x}", name, status));
+        }
+
+        Ok(attachments)
+    }
+}
+
+// Textures attached to the bound framebuffer
+unsafe fn create_color_textures(gl: &glow::Context, spec: &FramebufferSpec) -> Result<Vec<glow::Texture>, String> {
+    let mut textures = vec![];
+
+    for (index, format) in spec.color_attachments.iter().enumerate() {
+        let (internal_format, data_format, data_type) = format.to_gl();
+        let texture = gl.create_texture()?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+
+        gl.tex_image_2d(glow::TEXTURE_2D, 0, internal_format as i32, spec.width as i32, spec.height as i32, 0, data_format, data_type, None);
+
+        // Integer textures can't be filtered
+        let filter = if *format == ColorFormat::R32I { glow::NEAREST } else { glow::LINEAR };
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, filter as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, filter as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+
+        gl.framebuffer_texture_2d(glow::FRAMEBUFFER, glow::COLOR_ATTACHMENT0 + index as u32, glow::TEXTURE_2D, Some(texture), 0);
+        textures.push(texture);
+    }
+
+    if !textures.is_empty() {
+        let draw_buffers: Vec<u32> = (0..textures.len() as u32).map(|index| glow::COLOR_ATTACHMENT0 + index).collect();
+        gl.draw_buffers(&draw_buffers);
+    }
+
+    Ok(textures)
+}
+
+fn delete_attachments(gl: &glow::Context, attachments: &Attachments) {
+    unsafe {
+        for texture in &attachments.textures {
+            gl.delete_texture(*texture);
+        }
+
+        for renderbuffer in &attachments.renderbuffers {
+            gl.delete_renderbuffer(*renderbuffer);
+        }
+
+        if let Some(framebuffer) = attachments.resolve_framebuffer {
+            gl.delete_framebuffer(framebuffer);
+        }
+
+        gl.delete_framebuffer(attachments.framebuffer);
+    }
+}
@@ -0,0 +1,229 @@
+use std::{cell::{Cell, RefCell}, collections::VecDeque, rc::Rc};
+
+use glow::HasContext;
+
+use crate::logger::*;
+
+// Frames whose queries may still be waiting for the GPU, older ones are dropped
+const MAX_PENDING_FRAMES: usize = 8;
+
+struct PassTimer {
+    name: String,
+    // Queries of each frame, the current one at the back. A pass has several
+    // queries in a frame when it is interrupted by nested passes
+    frames: VecDeque<Vec<glow::Query>>,
+    // Milliseconds of the last frame with results, none when the pass was not drawn in it
+    last_time: Option<f32>
+}
+
+// GPU time of the render passes, measured with GL_TIME_ELAPSED queries that are read
+// some frames later so the CPU never waits for the GPU.
+// The time elapsed queries can't overlap, so a nested pass pauses the outer one and
+// its time is not part of the outer pass
+pub struct GpuTimers {
+    gl: Rc<glow::Context>,
+    supported: bool,
+    passes: RefCell<Vec<PassTimer>>,
+    // Passes that began and didn't end, the last one is being measured. A pass that began
+    // again before it ended is a None, so its end doesn't close the outer query
+    stack: RefCell<Vec<Option<usize>>>,
+    free_queries: RefCell<Vec<glow::Query>>,
+    warned: Cell<bool>
+}
+
+impl GpuTimers {
+    pub fn new(gl: Rc<glow::Context>) -> GpuTimers {
+        let version = gl.version();
+        let supported = (version.major, version.minor) >= (3, 3) || gl.supported_extensions().contains("GL_ARB_timer_query");
+
+        if !supported {
+            hds_core_warn!("GL timer queries are not supported, the GPU times are not measured");
+        }
+
+        GpuTimers {
+            gl,
+            supported,
+            passes: RefCell::new(vec![]),
+            stack: RefCell::new(vec![]),
+            free_queries: RefCell::new(vec![]),
+            warned: Cell::new(false)
+        }
+    }
+
+    pub fn begin(&self, name: &str) {
+        if !self.supported {
+            return;
+        }
+
+        let index = self.get_pass_index(name);
+        if self.stack.borrow().contains(&Some(index)) {
+            hds_core_warn!("GPU timer {} began again before it ended", name);
+            self.stack.borrow_mut().push(None);
+            return;
+        }
+
+        if !self.stack.borrow().is_empty() {
+            unsafe { self.gl.end_query(glow::TIME_ELAPSED) };
+        }
+
+        self.stack.borrow_mut().push(Some(index));
+        self.start_query(index);
+    }
+
+    pub fn end(&self) {
+        if !self.supported {
+            return;
+        }
+
+        match self.stack.borrow_mut().pop() {
+            Some(Some(_)) => {},
+            // The repeated pass is measured by its first begin
+            Some(None) => return,
+            None => {
+                hds_core_warn!("GPU timer ended without a pass");
+                return;
+            },
+        }
+
+        unsafe { self.gl.end_query(glow::TIME_ELAPSED) };
+
+        // The outer pass goes on
+        let outer = self.stack.borrow().iter().rev().flatten().next().copied();
+        if let Some(outer) = outer {
+            self.start_query(outer);
+        }
+    }
+
+    // Closes the queries of the frame and reads the results that are ready
+    pub fn end_frame(&self) {
+        if !self.supported {
+            return;
+        }
+
+        // Passes that didn't end are ended here, the warning is only shown once
+        if !self.stack.borrow().is_empty() && !self.warned.replace(true) {
+            hds_core_warn!("GPU timers still running at the end of the frame");
+        }
+
+        // Only the last pass has a running query
+        if self.stack.borrow_mut().drain(..).any(|entry| entry.is_some()) {
+            unsafe { self.gl.end_query(glow::TIME_ELAPSED) };
+        }
+
+        let mut free_queries = self.free_queries.borrow_mut();
+
+        for pass in self.passes.borrow_mut().iter_mut() {
+            pass.frames.push_back(vec![]);
+
+            // Every frame but the new one is finished, they are read in order until one isn't available
+            while pass.frames.len() > 1 {
+                let ready = pass.frames[0].iter().all(|query| unsafe {
+                    self.gl.get_query_parameter_u32(*query, glow::QUERY_RESULT_AVAILABLE) != 0
+                });
+
+                if !ready && pass.frames.len() <= MAX_PENDING_FRAMES {
+                    break;
+                }
+
+                let queries = pass.frames.pop_front().unwrap_or_default();
+                if ready {
+                    let nanoseconds: u64 = queries.iter()
+                        .map(|query| unsafe { self.gl.get_query_parameter_u32(*query, glow::QUERY_RESULT) } as u64)
+                        .sum();
+
+                    pass.last_time = (!queries.is_empty()).then_some(nanoseconds as f32 / 1_000_000.0);
+                }
+
+                free_queries.extend(queries);
+            }
+        }
+    }
+
+    // Milliseconds of each pass in the last frame with results
+    pub fn get_times(&self) -> Vec<(String, f32)> {
+        self.passes.borrow().iter()
+            .filter_map(|pass| pass.last_time.map(|time| (pass.name.clone(), time)))
+            .collect()
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.supported
+    }
+
+    fn get_pass_index(&self, name: &str) -> usize {
+        let mut passes = self.passes.borrow_mut();
+
+        match passes.iter().position(|pass| pass.name == name) {
+            Some(index) => index,
+            None => {
+                passes.push(PassTimer { name: name.to_string(), frames: VecDeque::from([vec![]]), last_time: None });
+                passes.len() - 1
+            },
+        }
+    }
+
+    fn start_query(&self, index: usize) {
+        let query = match self.free_queries.borrow_mut().pop() {
+            Some(query) => query,
+            None => match unsafe { self.gl.create_query() } {
+                Ok(query) => query,
+                Err(err) => {
+                    hds_core_error!("Failed to create GPU timer query: {}", err);
+                    return;
+                },
+            },
+        };
+
+        unsafe { self.gl.begin_query(glow::TIME_ELAPSED, query) };
+
+        if let Some(frame) = self.passes.borrow_mut()[index].frames.back_mut() {
+            frame.push(query);
+        }
+    }
+}
+
+impl Drop for GpuTimers {
+    fn drop(&mut self) {
+        let pending = self.passes.get_mut().iter_mut().flat_map(|pass| pass.frames.drain(..).flatten());
+        for query in pending.chain(self.free_queries.get_mut().drain(..)) {
+            unsafe { self.gl.delete_query(query) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glow::HasContext;
+
+    use crate::renderer::HeadlessContext;
+
+    use super::*;
+
+    #[test]
+    fn repeated_pass_keeps_the_outer_query() {
+        let (_context, api) = match HeadlessContext::new(true, false) {
+            Ok(headless) => headless,
+            Err(err) => {
+                eprintln!("Skipping the GPU timers: {}", err);
+                return;
+            },
+        };
+
+        let timers = GpuTimers::new(api.gl().clone());
+        if !timers.is_supported() {
+            return;
+        }
+
+        timers.begin("Outer");
+        timers.begin("Inner");
+        timers.begin("Outer");
+        timers.end();
+        timers.end();
+        assert_eq!(*timers.stack.borrow(), [Some(0)]);
+
+        timers.end();
+        timers.end_frame();
+        assert!(timers.stack.borrow().is_empty());
+        assert_eq!(unsafe { api.gl().get_error() }, glow::NO_ERROR);
+    }
+}
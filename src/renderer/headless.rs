@@ -0,0 +1,81 @@
+use std::ffi::CStr;
+
+use glutin::{
+    api::egl::{context::PossiblyCurrentContext, device::Device, display::Display},
+    config::{ConfigSurfaceTypes, ConfigTemplateBuilder},
+    context::{ContextApi, ContextAttributesBuilder, Version},
+    display::GlDisplay
+};
+
+use crate::logger::*;
+
+use super::OpenGLRendererAPI;
+
+// Extension of the Mesa software devices, like llvmpipe
+const SOFTWARE_EXTENSION: &str = "EGL_MESA_device_software";
+
+// GL context without window or surface, the frames are drawn into framebuffers
+pub struct HeadlessContext {
+    // Keeps the context current while the renderer lives
+    _context: PossiblyCurrentContext,
+    device_name: String
+}
+
+impl HeadlessContext {
+    // Software devices give the same pixels on every machine, which the image comparisons need
+    pub fn new(software_only: bool, debug: bool) -> Result<(HeadlessContext, OpenGLRendererAPI), String> {
+        let mut devices: Vec<Device> = Device::query_devices()
+            .map_err(|err| format!("Failed to query the EGL devices: {}", err))?
+            .filter(|device| !software_only || device.extensions().contains(SOFTWARE_EXTENSION))
+            .collect();
+
+        // Hardware devices first when both are allowed
+        devices.sort_by_key(|device| device.extensions().contains(SOFTWARE_EXTENSION));
+
+        let mut errors = vec![];
+        for device in devices {
+            // Mesa devices often have no name
+            let software = device.extensions().contains(SOFTWARE_EXTENSION);
+            let device_name = device.name().or(device.vendor()).unwrap_or(if software { "software device" } else { "hardware device" }).to_string();
+
+            match Self::create_context(&device, debug) {
+                Ok((context, render_command)) => {
+                    hds_core_info!("Headless GL context on {}", device_name);
+                    return Ok((HeadlessContext { _context: context, device_name }, render_command));
+                },
+                Err(err) => errors.push(format!("{}: {}", device_name, err)),
+            }
+        }
+
+        if errors.is_empty() {
+            errors.push(if software_only { "No software EGL device" } else { "No EGL device" }.to_string());
+        }
+
+        Err(format!("Failed to create a headless GL context ({})", errors.join(", ")))
+    }
+
+    fn create_context(device: &Device, debug: bool) -> Result<(PossiblyCurrentContext, OpenGLRendererAPI), String> {
+        let display = unsafe { Display::with_device(device, None) }.map_err(|err| err.to_string())?;
+
+        let template = ConfigTemplateBuilder::new().with_surface_type(ConfigSurfaceTypes::empty()).build();
+        let config = unsafe { display.find_configs(template) }.map_err(|err| err.to_string())?
+            .next()
+            .ok_or("No config without surface")?;
+
+        let attributes = ContextAttributesBuilder::new()
+            .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+            .with_debug(debug)
+            .build(None);
+
+        let context = unsafe { display.create_context(&config, &attributes) }.map_err(|err| err.to_string())?
+            .make_current_surfaceless()
+            .map_err(|err| err.to_string())?;
+
+        let gl = unsafe { glow::Context::from_loader_function_cstr(|name: &CStr| display.get_proc_address(name).cast()) };
+        Ok((context, OpenGLRendererAPI::from_glow(gl)))
+    }
+
+    pub fn get_device_name(&self) -> &str {
+        &self.device_name
+    }
+}
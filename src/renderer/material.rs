@@ -0,0 +1,450 @@
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc
+};
+
+use crate::{logger::*, math::{Mat4, Vec2, Vec3, Vec4}};
+
+use super::{
+    buffer::as_bytes,
+    uniform_buffer::{MATERIAL_BINDING, MATERIAL_BLOCK},
+    BlendMode, RenderCommand, RendererAPI, Shader, ShaderLibrary, Texture2D, TextureLibrary, TextureSettings, UniformBuffer
+};
+
+// Value of a material parameter, the textures are read by the sampler uniforms and
+// the other values by the members of the Material uniform block of the shader
+#[derive(Clone)]
+pub enum MaterialValue {
+    Float(f32),
+    Float2(Vec2),
+    // Also the rgb colors
+    Float3(Vec3),
+    // Also the rgba colors
+    Float4(Vec4),
+    Int(i32),
+    Mat4(Mat4),
+    Texture(Rc<Texture2D>)
+}
+
+impl MaterialValue {
+    pub fn get_type_name(&self) -> &'static str {
+        match self {
+            MaterialValue::Float(_) => "float",
+            MaterialValue::Float2(_) => "vec2",
+            MaterialValue::Float3(_) => "vec3",
+            MaterialValue::Float4(_) => "vec4",
+            MaterialValue::Int(_) => "int",
+            MaterialValue::Mat4(_) => "mat4",
+            MaterialValue::Texture(_) => "sampler2D",
+        }
+    }
+
+    // Whether the value can be read by a uniform of the GL type
+    fn matches(&self, gl_type: u32) -> bool {
+        matches!((self, gl_type),
+            (MaterialValue::Float(_), glow::FLOAT) |
+            (MaterialValue::Float2(_), glow::FLOAT_VEC2) |
+            (MaterialValue::Float3(_), glow::FLOAT_VEC3) |
+            (MaterialValue::Float4(_), glow::FLOAT_VEC4) |
+            (MaterialValue::Int(_), glow::INT | glow::BOOL) |
+            (MaterialValue::Mat4(_), glow::FLOAT_MAT4) |
+            (MaterialValue::Texture(_), glow::SAMPLER_2D))
+    }
+
+    // Writes the value at the start of the data in the std140 layout
+    fn write(&self, data: &mut [u8]) {
+        let bytes = match self {
+            MaterialValue::Float(value) => value.to_ne_bytes().to_vec(),
+            MaterialValue::Float2(value) => as_bytes(&value.to_array()).to_vec(),
+            MaterialValue::Float3(value) => as_bytes(&value.to_array()).to_vec(),
+            MaterialValue::Float4(value) => as_bytes(&value.to_array()).to_vec(),
+            MaterialValue::Int(value) => value.to_ne_bytes().to_vec(),
+            MaterialValue::Mat4(value) => as_bytes(&value.to_cols_array()).to_vec(),
+            MaterialValue::Texture(_) => return,
+        };
+
+        if let Some(target) = data.get_mut(..bytes.len()) {
+            target.copy_from_slice(&bytes);
+        }
+    }
+}
+
+// Shader with the values of its parameters, drawn through the instances created from it
+pub struct Material {
+    render_command: RenderCommand,
+    name: String,
+    shader: Rc<Shader>,
+    // None keeps the blend mode that is set
+    blend_mode: Option<BlendMode>,
+    values: Vec<(String, MaterialValue)>,
+    path: Option<PathBuf>
+}
+
+impl Material {
+    pub fn new(render_command: &RenderCommand, name: &str, shader: Rc<Shader>) -> Material {
+        Material { render_command: render_command.clone(), name: name.to_string(), shader, blend_mode: None, values: vec![], path: None }
+    }
+
+    // Text definition, the paths of the shader and the textures are relative to the directory.
+    // The shaders and the textures are loaded by the libraries so the materials share them
+    pub fn from_source(
+        render_command: &RenderCommand,
+        name: &str,
+        source: &str,
+        directory: &Path,
+        shader_library: &mut ShaderLibrary,
+        texture_library: &mut TextureLibrary
+    ) -> Result<Material, String> {
+        let report = |err: String| {
+            let message = format!("Material '{}': {}", name, err);
+            hds_core_error!("{}", message);
+            message
+        };
+
+        let definition = parse_material(source).map_err(report)?;
+        let shader = shader_library.get_or_load(directory.join(&definition.shader))?;
+
+        let mut material = Material::new(render_command, name, shader);
+        material.blend_mode = definition.blend_mode;
+
+        for (parameter, value) in definition.parameters {
+            let value = match value {
+                ParsedValue::Value(value) => value,
+                ParsedValue::Texture(path) => MaterialValue::Texture(texture_library.load(directory.join(path), TextureSettings::default())?),
+            };
+
+            material.set(&parameter, value);
+        }
+
+        Ok(material)
+    }
+
+    // Loads a file in the same format as from_source,
+    // the material is named after the file
+    pub fn from_file(
+        render_command: &RenderCommand,
+        path: impl AsRef<Path>,
+        shader_library: &mut ShaderLibrary,
+        texture_library: &mut TextureLibrary
+    ) -> Result<Material, String> {
+        let path = path.as_ref();
+        let name = path.file_stem().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string());
+
+        let source = fs::read_to_string(path).map_err(|err| {
+            let message = format!("Failed to read material '{}': {}", path.display(), err);
+            hds_core_error!("{}", message);
+            message
+        })?;
+
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let mut material = Self::from_source(render_command, &name, &source, directory, shader_library, texture_library)?;
+        material.path = Some(path.to_path_buf());
+        Ok(material)
+    }
+
+    // Value used by the instances that don't override it
+    pub fn set(&mut self, name: &str, value: MaterialValue) {
+        check_parameter(&self.shader, name, &value);
+        set_value(&mut self.values, name, value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MaterialValue> {
+        self.values.iter().find(|(other, _)| other == name).map(|(_, value)| value)
+    }
+
+    pub fn create_instance(self: &Rc<Self>) -> MaterialInstance {
+        MaterialInstance { material: self.clone(), overrides: vec![], buffer: RefCell::new(None), uploaded: RefCell::new(vec![]) }
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: Option<BlendMode>) {
+        self.blend_mode = blend_mode;
+    }
+
+    pub fn get_blend_mode(&self) -> Option<BlendMode> {
+        self.blend_mode
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_shader(&self) -> &Rc<Shader> {
+        &self.shader
+    }
+
+    pub fn get_path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+// Values of one object drawn with a material, the ones it doesn't set come from the material.
+// Each instance has its own uniform buffer, written when its values change
+pub struct MaterialInstance {
+    material: Rc<Material>,
+    overrides: Vec<(String, MaterialValue)>,
+    // Created at the size of the Material block, again if a shader reload changes it
+    buffer: RefCell<Option<UniformBuffer>>,
+    // Data of the last upload
+    uploaded: RefCell<Vec<u8>>
+}
+
+impl MaterialInstance {
+    pub fn set(&mut self, name: &str, value: MaterialValue) {
+        check_parameter(&self.material.shader, name, &value);
+        set_value(&mut self.overrides, name, value);
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        self.set(name, MaterialValue::Float(value));
+    }
+
+    pub fn set_float2(&mut self, name: &str, value: Vec2) {
+        self.set(name, MaterialValue::Float2(value));
+    }
+
+    pub fn set_float3(&mut self, name: &str, value: Vec3) {
+        self.set(name, MaterialValue::Float3(value));
+    }
+
+    pub fn set_float4(&mut self, name: &str, value: Vec4) {
+        self.set(name, MaterialValue::Float4(value));
+    }
+
+    pub fn set_color(&mut self, name: &str, color: Vec4) {
+        self.set(name, MaterialValue::Float4(color));
+    }
+
+    pub fn set_int(&mut self, name: &str, value: i32) {
+        self.set(name, MaterialValue::Int(value));
+    }
+
+    pub fn set_mat4(&mut self, name: &str, value: Mat4) {
+        self.set(name, MaterialValue::Mat4(value));
+    }
+
+    pub fn set_texture(&mut self, name: &str, texture: Rc<Texture2D>) {
+        self.set(name, MaterialValue::Texture(texture));
+    }
+
+    // Goes back to the value of the material
+    pub fn reset(&mut self, name: &str) {
+        self.overrides.retain(|(other, _)| other != name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MaterialValue> {
+        self.overrides.iter().find(|(other, _)| other == name).map(|(_, value)| value).or_else(|| self.material.get(name))
+    }
+
+    pub fn get_material(&self) -> &Rc<Material> {
+        &self.material
+    }
+
+    // Binds the shader, the uniform buffer and the textures for the next draws
+    pub fn bind(&self) {
+        let material = &self.material;
+        material.shader.bind();
+
+        if let Some(blend_mode) = material.blend_mode {
+            material.render_command.set_blend_mode(blend_mode);
+        }
+
+        if let Some(block) = material.shader.get_uniform_block(MATERIAL_BLOCK) {
+            // The members without a value are zero
+            let mut data = vec![0; block.size];
+            for member in block.members.iter() {
+                if let Some(value) = self.get(&member.name).filter(|value| value.matches(member.gl_type)) {
+                    value.write(&mut data[member.offset..]);
+                }
+            }
+
+            let mut buffer = self.buffer.borrow_mut();
+            let mut uploaded = self.uploaded.borrow_mut();
+
+            if buffer.as_ref().map(UniformBuffer::get_size) != Some(block.size) {
+                *buffer = Some(UniformBuffer::new(&material.render_command, block.size));
+                uploaded.clear();
+            }
+
+            if let Some(buffer) = buffer.as_ref() {
+                if *uploaded != data {
+                    buffer.set_data(0, &data);
+                    *uploaded = data;
+                }
+
+                buffer.bind(MATERIAL_BINDING);
+            }
+        }
+
+        // The textures take the slots in the order of the samplers
+        let samplers: Vec<String> = material.shader.get_uniforms().iter()
+            .filter(|uniform| uniform.gl_type == glow::SAMPLER_2D && uniform.size == 1)
+            .map(|uniform| uniform.name.clone())
+            .collect();
+
+        for (slot, sampler) in samplers.iter().enumerate() {
+            if let Some(MaterialValue::Texture(texture)) = self.get(sampler) {
+                texture.bind(slot as u32);
+                material.shader.set_texture_slot(sampler, slot as u32);
+            }
+        }
+    }
+}
+
+fn set_value(values: &mut Vec<(String, MaterialValue)>, name: &str, value: MaterialValue) {
+    match values.iter_mut().find(|(other, _)| other == name) {
+        Some((_, other)) => *other = value,
+        None => values.push((name.to_string(), value)),
+    }
+}
+
+// The value is kept even when the shader doesn't read it, a reload of the shader can add it
+fn check_parameter(shader: &Shader, name: &str, value: &MaterialValue) {
+    let gl_type = match value {
+        MaterialValue::Texture(_) => shader.get_uniforms().iter().find(|uniform| uniform.name == name).map(|uniform| uniform.gl_type),
+        _ => shader.get_uniform_block(MATERIAL_BLOCK).and_then(|block| block.get_member(name).map(|member| member.gl_type)),
+    };
+
+    match gl_type {
+        Some(gl_type) if value.matches(gl_type) => {},
+        Some(_) => hds_core_warn!("Parameter '{}' of shader '{}' doesn't have the type {}", name, shader.get_name(), value.get_type_name()),
+        None => hds_core_warn!("Shader '{}' has no material parameter '{}'", shader.get_name(), name),
+    }
+}
+
+enum ParsedValue {
+    Value(MaterialValue),
+    Texture(String)
+}
+
+struct MaterialDefinition {
+    shader: String,
+    blend_mode: Option<BlendMode>,
+    parameters: Vec<(String, ParsedValue)>
+}
+
+// Parses a material file, one setting per line:
+//
+// # Comment
+// shader = lit.glsl
+// blend = alpha
+// color u_Color = 1.0 0.5 0.2
+// float u_Roughness = 0.4
+// texture u_Albedo = textures/albedo.png
+//
+// The parameter types are float, float2, float3, float4, color (3 numbers for a vec3, 4 for a vec4), int and texture
+fn parse_material(source: &str) -> Result<MaterialDefinition, String> {
+    let mut shader = None;
+    let mut blend_mode = None;
+    let mut parameters = vec![];
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |message: String| format!("line {}: {}", index + 1, message);
+
+        let (key, value) = line.split_once('=').ok_or_else(|| error(format!("expected '=' in '{}'", line)))?;
+        let (key, value) = (key.trim(), value.trim());
+
+        let mut words = key.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some("shader"), None, _) => shader = Some(value.to_string()),
+            (Some("blend"), None, _) => blend_mode = Some(parse_blend_mode(value).ok_or_else(|| error(format!("unknown blend mode '{}'", value)))?),
+            (Some(value_type), Some(name), None) => {
+                let value = parse_value(value_type, value).map_err(error)?;
+                parameters.push((name.to_string(), value));
+            },
+            _ => return Err(error(format!("expected a setting or '<type> <name>' before '=' in '{}'", line))),
+        }
+    }
+
+    let shader = shader.ok_or("no shader = <path> line")?;
+    Ok(MaterialDefinition { shader, blend_mode, parameters })
+}
+
+fn parse_blend_mode(name: &str) -> Option<BlendMode> {
+    match name {
+        "none" => Some(BlendMode::None),
+        "alpha" => Some(BlendMode::Alpha),
+        "premultiplied" => Some(BlendMode::Premultiplied),
+        "additive" => Some(BlendMode::Additive),
+        _ => None,
+    }
+}
+
+fn parse_value(value_type: &str, text: &str) -> Result<ParsedValue, String> {
+    if value_type == "texture" {
+        return match text {
+            "" => Err("expected the path of the texture".to_string()),
+            _ => Ok(ParsedValue::Texture(text.to_string())),
+        };
+    }
+
+    if value_type == "int" {
+        return text.parse().map(|value| ParsedValue::Value(MaterialValue::Int(value))).map_err(|_| format!("'{}' is not an integer", text));
+    }
+
+    let numbers = text.split_whitespace()
+        .map(|number| number.parse::<f32>().map_err(|_| format!("'{}' is not a number", number)))
+        .collect::<Result<Vec<f32>, String>>()?;
+
+    let value = match (value_type, numbers.as_slice()) {
+        ("float", [x]) => MaterialValue::Float(*x),
+        ("float2", [x, y]) => MaterialValue::Float2(Vec2::new(*x, *y)),
+        ("float3", [x, y, z]) => MaterialValue::Float3(Vec3::new(*x, *y, *z)),
+        ("float4", [x, y, z, w]) => MaterialValue::Float4(Vec4::new(*x, *y, *z, *w)),
+        ("color", [r, g, b]) => MaterialValue::Float3(Vec3::new(*r, *g, *b)),
+        ("color", [r, g, b, a]) => MaterialValue::Float4(Vec4::new(*r, *g, *b, *a)),
+        ("float" | "float2" | "float3" | "float4" | "color", _) => return Err(format!("wrong number of values for {}: '{}'", value_type, text)),
+        _ => return Err(format!("unknown parameter type '{}'", value_type)),
+    };
+
+    Ok(ParsedValue::Value(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_material_settings() {
+        let definition = parse_material("
+            # Comment
+            shader = shaders/lit.glsl
+            blend = alpha
+            color u_Color = 1.0 0.5 0.2
+            float2 u_Offset = -1 2
+            int u_Mode = 3
+            texture u_Albedo = textures/albedo.png
+            color u_Tint = 1 1 1 0.5
+        ").unwrap();
+
+        assert_eq!(definition.shader, "shaders/lit.glsl");
+        assert_eq!(definition.blend_mode, Some(BlendMode::Alpha));
+
+        let names: Vec<&str> = definition.parameters.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["u_Color", "u_Offset", "u_Mode", "u_Albedo", "u_Tint"]);
+
+        assert!(matches!(&definition.parameters[0].1, ParsedValue::Value(MaterialValue::Float3(color)) if *color == Vec3::new(1.0, 0.5, 0.2)));
+        assert!(matches!(&definition.parameters[1].1, ParsedValue::Value(MaterialValue::Float2(offset)) if *offset == Vec2::new(-1.0, 2.0)));
+        assert!(matches!(&definition.parameters[2].1, ParsedValue::Value(MaterialValue::Int(3))));
+        assert!(matches!(&definition.parameters[3].1, ParsedValue::Texture(path) if path == "textures/albedo.png"));
+        assert!(matches!(&definition.parameters[4].1, ParsedValue::Value(MaterialValue::Float4(tint)) if *tint == Vec4::new(1.0, 1.0, 1.0, 0.5)));
+    }
+
+    #[test]
+    fn parse_material_errors() {
+        let error = |source: &str| parse_material(source).err().unwrap_or_default();
+
+        assert_eq!(error("blend = alpha"), "no shader = <path> line");
+        assert!(error("shader = a.glsl\nblend = glow").starts_with("line 2: unknown blend mode"));
+        assert!(error("shader = a.glsl\nfloat3 u_Position = 1 2").starts_with("line 2: wrong number of values"));
+        assert!(error("shader = a.glsl\nfloat u_Value = x").starts_with("line 2: 'x' is not a number"));
+        assert!(error("shader = a.glsl\nvec3 u_Value = 1 2 3").starts_with("line 2: unknown parameter type"));
+        assert!(error("shader a.glsl").starts_with("line 1: expected '='"));
+    }
+}
@@ -0,0 +1,41 @@
+pub mod buffer;
+pub mod camera;
+pub mod camera_controller;
+pub mod capture;
+pub mod debug;
+pub mod framebuffer;
+pub mod gpu_timer;
+pub mod headless;
+pub mod material;
+pub mod opengl;
+pub mod renderer2d;
+pub mod renderer_api;
+pub mod shader;
+pub mod shader_library;
+pub mod stats;
+pub mod texture;
+pub mod uniform_buffer;
+pub mod vertex_array;
+
+use std::rc::Rc;
+
+pub use buffer::{BufferElement, BufferLayout, BufferUsage, IndexBuffer, ShaderDataType, VertexBuffer};
+pub use camera::{Camera, OrthographicCamera, PerspectiveCamera};
+pub use camera_controller::{FlyCameraController, OrthographicCameraController};
+pub use framebuffer::{ColorFormat, DepthFormat, Framebuffer, FramebufferSpec};
+pub use gpu_timer::GpuTimers;
+pub use headless::HeadlessContext;
+pub use material::{Material, MaterialInstance, MaterialValue};
+pub use opengl::OpenGLRendererAPI;
+pub use renderer2d::{Renderer2D, Renderer2DStats};
+pub use renderer_api::{BlendMode, DepthFunc, RendererAPI};
+pub use shader::{BlockMember, Shader, ShaderResource, ShaderStage, StageSource, UniformBlock};
+pub use shader_library::ShaderLibrary;
+pub use stats::{RenderStats, StatsCounter};
+pub use texture::{Texture2D, TextureFilter, TextureLibrary, TextureSettings, TextureWrap};
+pub use uniform_buffer::{CameraBuffer, CameraData, UniformBuffer};
+pub use vertex_array::VertexArray;
+
+// Handle to the renderer shared by the application and the layers, all of them
+// use the same GL context so the state tracking stays valid
+pub type RenderCommand = Rc<OpenGLRendererAPI>;
@@ -0,0 +1,272 @@
+use std::{cell::Cell, rc::Rc};
+
+use glow::HasContext;
+use glutin::{
+    context::PossiblyCurrentContext,
+    display::{GetGlDisplay, GlDisplay}
+};
+
+use crate::logger::*;
+
+use super::{debug, BlendMode, DepthFunc, GpuTimers, RenderStats, RendererAPI, StatsCounter, VertexArray};
+
+// Last values sent to GL, commands that don't change them are skipped
+struct GlState {
+    clear_color: Cell<Option<[f32; 4]>>,
+    viewport: Cell<(i32, i32, u32, u32)>,
+    blend_mode: Cell<Option<BlendMode>>,
+    depth_test: Cell<Option<Option<DepthFunc>>>,
+    depth_write: Cell<Option<bool>>,
+    line_width: Cell<Option<f32>>
+}
+
+pub struct OpenGLRendererAPI {
+    gl: Rc<glow::Context>,
+    state: GlState,
+    // The driver messages go to the logger
+    debug_output: bool,
+    // glGetError after every call, only in debug builds
+    error_checks: Cell<bool>,
+    stats: Rc<StatsCounter>,
+    timers: GpuTimers,
+    // Size of the default framebuffer, the framebuffers that follow the window take it
+    window_size: Cell<(u32, u32)>
+}
+
+impl OpenGLRendererAPI {
+    // Loads the GL functions of the context, it must be current in this thread
+    pub fn new(context: &PossiblyCurrentContext) -> OpenGLRendererAPI {
+        let gl = unsafe {
+            glow::Context::from_loader_function_cstr(|s| context.display().get_proc_address(s).cast())
+        };
+
+        Self::from_glow(gl)
+    }
+
+    pub fn from_glow(mut gl: glow::Context) -> OpenGLRendererAPI {
+        let (version, renderer, vendor) = unsafe {
+            (gl.get_parameter_string(glow::VERSION), gl.get_parameter_string(glow::RENDERER), gl.get_parameter_string(glow::VENDOR))
+        };
+
+        hds_core_info!("OpenGL {} ({}, {})", version, renderer, vendor);
+        set_crash_info("GL", format!("{} ({}, {})", version, renderer, vendor));
+
+        let viewport = unsafe {
+            let mut viewport = [0; 4];
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut viewport);
+            (viewport[0], viewport[1], viewport[2].max(0) as u32, viewport[3].max(0) as u32)
+        };
+
+        let state = GlState {
+            clear_color: Cell::new(None),
+            viewport: Cell::new(viewport),
+            blend_mode: Cell::new(None),
+            depth_test: Cell::new(None),
+            depth_write: Cell::new(None),
+            line_width: Cell::new(None)
+        };
+
+        let debug_output = debug::enable_debug_output(&mut gl);
+        let gl = Rc::new(gl);
+        let timers = GpuTimers::new(gl.clone());
+
+        let window_size = Cell::new((viewport.2, viewport.3));
+        OpenGLRendererAPI { gl, state, debug_output, error_checks: Cell::new(false), stats: Rc::default(), timers, window_size }
+    }
+
+    // Raw context for the GL resources like buffers, shaders or textures
+    pub fn gl(&self) -> &Rc<glow::Context> {
+        &self.gl
+    }
+
+    // True when the context was created with debug enabled and the driver supports KHR_debug
+    pub fn is_debug_output(&self) -> bool {
+        self.debug_output
+    }
+
+    // Called by the application when the window, or the target of a headless application, changes size
+    pub fn set_window_size(&self, width: u32, height: u32) {
+        self.window_size.set((width, height));
+    }
+
+    pub fn get_window_size(&self) -> (u32, u32) {
+        self.window_size.get()
+    }
+
+    // Each check waits for the GPU, so release builds never check
+    pub fn set_error_checks(&self, enabled: bool) {
+        if enabled && !cfg!(debug_assertions) {
+            hds_core_warn!("GL error checks are only available in debug builds");
+            return;
+        }
+
+        self.error_checks.set(enabled);
+    }
+
+    pub fn is_error_checks(&self) -> bool {
+        self.error_checks.get()
+    }
+
+    // Stats of the last frame that ended
+    pub fn get_stats(&self) -> RenderStats {
+        self.stats.get_last_frame()
+    }
+
+    // The GL objects count their binds and uploads in the stats of the renderer that created them
+    pub fn get_stats_counter(&self) -> &Rc<StatsCounter> {
+        &self.stats
+    }
+
+    // Measures the GPU time of the commands until end_gpu_timer, passes can be nested
+    pub fn begin_gpu_timer(&self, name: &str) {
+        self.timers.begin(name);
+    }
+
+    pub fn end_gpu_timer(&self) {
+        self.timers.end();
+    }
+
+    // Milliseconds of each pass, from a few frames ago because the results are read without waiting
+    pub fn get_gpu_times(&self) -> Vec<(String, f32)> {
+        self.timers.get_times()
+    }
+
+    // Called once the frame is submitted
+    pub fn end_frame(&self) {
+        self.stats.end_frame();
+        self.timers.end_frame();
+    }
+
+    // Logs the GL errors of the last calls with the place in the code that made them, when the checks are enabled
+    #[track_caller]
+    pub fn check_errors(&self, call: &str) {
+        if self.error_checks.get() {
+            debug::check_errors(&self.gl, call);
+        }
+    }
+
+}
+
+impl RendererAPI for OpenGLRendererAPI {
+    fn set_clear_color(&self, color: [f32; 4]) {
+        if self.state.clear_color.replace(Some(color)) != Some(color) {
+            unsafe { self.gl.clear_color(color[0], color[1], color[2], color[3]) };
+            self.check_errors("glClearColor");
+        }
+    }
+
+    fn clear(&self) {
+        unsafe { self.gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT | glow::STENCIL_BUFFER_BIT) };
+        self.check_errors("glClear");
+    }
+
+    fn set_viewport(&self, x: i32, y: i32, width: u32, height: u32) {
+        let viewport = (x, y, width, height);
+        if self.state.viewport.replace(viewport) != viewport {
+            unsafe { self.gl.viewport(x, y, width as i32, height as i32) };
+            self.stats.add_state_change();
+            self.check_errors("glViewport");
+        }
+    }
+
+    fn get_viewport(&self) -> (i32, i32, u32, u32) {
+        self.state.viewport.get()
+    }
+
+    fn draw_indexed(&self, vertex_array: &VertexArray, index_count: Option<u32>) {
+        let count = match (index_count, vertex_array.get_index_buffer()) {
+            (Some(count), Some(_)) => count,
+            (None, Some(index_buffer)) => index_buffer.get_count(),
+            (_, None) => {
+                hds_core_error!("Indexed draw of a vertex array without an index buffer");
+                return;
+            },
+        };
+
+        vertex_array.bind();
+        unsafe { self.gl.draw_elements(glow::TRIANGLES, count as i32, glow::UNSIGNED_INT, 0) };
+        self.stats.add_draw_call(count as u64);
+        self.check_errors("glDrawElements");
+    }
+
+    fn draw_lines(&self, vertex_array: &VertexArray, vertex_count: u32) {
+        vertex_array.bind();
+        unsafe { self.gl.draw_arrays(glow::LINES, 0, vertex_count as i32) };
+        self.stats.add_draw_call(vertex_count as u64);
+        self.check_errors("glDrawArrays");
+    }
+
+    fn set_line_width(&self, width: f32) {
+        if self.state.line_width.replace(Some(width)) != Some(width) {
+            unsafe { self.gl.line_width(width) };
+            self.stats.add_state_change();
+            self.check_errors("glLineWidth");
+        }
+    }
+
+    fn set_blend_mode(&self, mode: BlendMode) {
+        if self.state.blend_mode.replace(Some(mode)) == Some(mode) {
+            return;
+        }
+
+        unsafe {
+            match mode {
+                BlendMode::None => self.gl.disable(glow::BLEND),
+                BlendMode::Alpha => {
+                    self.gl.enable(glow::BLEND);
+                    self.gl.blend_equation(glow::FUNC_ADD);
+                    self.gl.blend_func_separate(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA, glow::ONE, glow::ONE_MINUS_SRC_ALPHA);
+                },
+                BlendMode::Premultiplied => {
+                    self.gl.enable(glow::BLEND);
+                    self.gl.blend_equation(glow::FUNC_ADD);
+                    self.gl.blend_func(glow::ONE, glow::ONE_MINUS_SRC_ALPHA);
+                },
+                BlendMode::Additive => {
+                    self.gl.enable(glow::BLEND);
+                    self.gl.blend_equation(glow::FUNC_ADD);
+                    self.gl.blend_func(glow::SRC_ALPHA, glow::ONE);
+                },
+            }
+        }
+
+        self.stats.add_state_change();
+        self.check_errors("set_blend_mode");
+    }
+
+    fn set_depth_test(&self, func: Option<DepthFunc>) {
+        if self.state.depth_test.replace(Some(func)) == Some(func) {
+            return;
+        }
+
+        unsafe {
+            match func {
+                Some(func) => {
+                    self.gl.enable(glow::DEPTH_TEST);
+                    self.gl.depth_func(match func {
+                        DepthFunc::Never        => glow::NEVER,
+                        DepthFunc::Less         => glow::LESS,
+                        DepthFunc::Equal        => glow::EQUAL,
+                        DepthFunc::LessEqual    => glow::LEQUAL,
+                        DepthFunc::Greater      => glow::GREATER,
+                        DepthFunc::NotEqual     => glow::NOTEQUAL,
+                        DepthFunc::GreaterEqual => glow::GEQUAL,
+                        DepthFunc::Always       => glow::ALWAYS
+                    });
+                },
+                None => self.gl.disable(glow::DEPTH_TEST),
+            }
+        }
+
+        self.stats.add_state_change();
+        self.check_errors("set_depth_test");
+    }
+
+    fn set_depth_write(&self, enabled: bool) {
+        if self.state.depth_write.replace(Some(enabled)) != Some(enabled) {
+            unsafe { self.gl.depth_mask(enabled) };
+            self.stats.add_state_change();
+            self.check_errors("glDepthMask");
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use std::{mem, rc::Rc};
+
+use bytemuck::{Pod, Zeroable};
+use crate::math::{Mat4, Transform, Vec2, Vec3, Vec4};
+
+use super::{
+    BlendMode, BufferElement, Camera, CameraBuffer, BufferLayout, BufferUsage, IndexBuffer, RenderCommand, RendererAPI, Shader,
+    ShaderDataType, Texture2D, TextureSettings, VertexArray, VertexBuffer
+};
+
+// Limits of one batch, reaching them draws the batch and starts another
+const MAX_QUADS: usize = 10000;
+const MAX_VERTICES: usize = MAX_QUADS * 4;
+const MAX_LINES: usize = 10000;
+// Must match the size of the sampler array of the quad shader
+const MAX_TEXTURE_SLOTS: usize = 16;
+
+// Corners of the quads, counter clockwise from the bottom left
+const QUAD_POSITIONS: [Vec3; 4] = [Vec3::new(-0.5, -0.5, 0.0), Vec3::new(0.5, -0.5, 0.0), Vec3::new(0.5, 0.5, 0.0), Vec3::new(-0.5, 0.5, 0.0)];
+// The first row of the textures is the top of the image
+const QUAD_TEX_COORDS: [[f32; 2]; 4] = [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct QuadVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+    tex_coord: [f32; 2],
+    tex_index: f32,
+    tiling: f32
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CircleVertex {
+    world_position: [f32; 3],
+    local_position: [f32; 3],
+    color: [f32; 4],
+    thickness: f32,
+    fade: f32
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct LineVertex {
+    position: [f32; 3],
+    color: [f32; 4]
+}
+
+// The vertices are only floats, without padding
+unsafe impl Zeroable for QuadVertex {}
+unsafe impl Pod for QuadVertex {}
+unsafe impl Zeroable for CircleVertex {}
+unsafe impl Pod for CircleVertex {}
+unsafe impl Zeroable for LineVertex {}
+unsafe impl Pod for LineVertex {}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Renderer2DStats {
+    pub draw_calls: u32,
+    pub quad_count: u32,
+    pub circle_count: u32,
+    pub line_count: u32
+}
+
+impl Renderer2DStats {
+    pub fn get_vertex_count(&self) -> u32 {
+        (self.quad_count + self.circle_count) * 4 + self.line_count * 2
+    }
+
+    pub fn get_index_count(&self) -> u32 {
+        (self.quad_count + self.circle_count) * 6
+    }
+}
+
+// One kind of primitive with its GL objects and the vertices of the current batch
+struct Batch<V> {
+    vertex_array: VertexArray,
+    shader: Shader,
+    vertices: Vec<V>
+}
+
+impl<V: Pod> Batch<V> {
+    fn new(render_command: &RenderCommand, shader: Shader, layout: BufferLayout, max_vertices: usize, indices: Option<&[u32]>) -> Batch<V> {
+        let mut vertex_array = VertexArray::new(render_command);
+
+        let mut vertex_buffer = VertexBuffer::with_size(render_command, max_vertices * mem::size_of::<V>(), BufferUsage::Stream);
+        vertex_buffer.set_layout(layout);
+        vertex_array.add_vertex_buffer(vertex_buffer);
+
+        if let Some(indices) = indices {
+            vertex_array.set_index_buffer(IndexBuffer::new(render_command, indices, BufferUsage::Static));
+        }
+
+        Batch { vertex_array, shader, vertices: Vec::with_capacity(max_vertices) }
+    }
+
+    // Sends the vertices to the GPU, false when there is nothing to draw
+    fn upload(&mut self) -> bool {
+        if self.vertices.is_empty() {
+            return false;
+        }
+
+        self.vertex_array.get_vertex_buffers()[0].set_data(0, &self.vertices);
+        self.shader.bind();
+        true
+    }
+}
+
+// Draws 2D primitives in as few draw calls as possible, between begin_scene and end_scene.
+// A batch holds one kind of primitive, it is drawn when the kind changes so the primitives
+// keep the order they were drawn in
+pub struct Renderer2D {
+    render_command: RenderCommand,
+    quads: Batch<QuadVertex>,
+    circles: Batch<CircleVertex>,
+    lines: Batch<LineVertex>,
+    // Camera block of the three shaders
+    camera_buffer: CameraBuffer,
+    // Slot 0 is a white texture used by the colored quads
+    texture_slots: Vec<Rc<Texture2D>>,
+    line_width: f32,
+    stats: Renderer2DStats
+}
+
+impl Renderer2D {
+    pub fn new(render_command: RenderCommand) -> Result<Renderer2D, String> {
+        // Two triangles per quad, the same for every quad
+        let indices: Vec<u32> = (0..MAX_QUADS as u32)
+            .flat_map(|quad| [0, 1, 2, 2, 3, 0].map(|index| quad * 4 + index))
+            .collect();
+
+        let quad_shader = Shader::from_source(&render_command, "Renderer2D_Quad", include_str!("shaders/renderer2d_quad.glsl"))?;
+        let slots: Vec<i32> = (0..MAX_TEXTURE_SLOTS as i32).collect();
+        quad_shader.set_int_array("u_Textures", &slots);
+
+        let quads = Batch::new(&render_command, quad_shader, BufferLayout::new(vec![
+            BufferElement::new(ShaderDataType::Float3, "a_Position"),
+            BufferElement::new(ShaderDataType::Float4, "a_Color"),
+            BufferElement::new(ShaderDataType::Float2, "a_TexCoord"),
+            BufferElement::new(ShaderDataType::Float, "a_TexIndex"),
+            BufferElement::new(ShaderDataType::Float, "a_Tiling")
+        ]), MAX_VERTICES, Some(&indices));
+
+        let circles = Batch::new(&render_command,
+            Shader::from_source(&render_command, "Renderer2D_Circle", include_str!("shaders/renderer2d_circle.glsl"))?,
+            BufferLayout::new(vec![
+                BufferElement::new(ShaderDataType::Float3, "a_WorldPosition"),
+                BufferElement::new(ShaderDataType::Float3, "a_LocalPosition"),
+                BufferElement::new(ShaderDataType::Float4, "a_Color"),
+                BufferElement::new(ShaderDataType::Float, "a_Thickness"),
+                BufferElement::new(ShaderDataType::Float, "a_Fade")
+            ]), MAX_VERTICES, Some(&indices));
+
+        let lines = Batch::new(&render_command,
+            Shader::from_source(&render_command, "Renderer2D_Line", include_str!("shaders/renderer2d_line.glsl"))?,
+            BufferLayout::new(vec![
+                BufferElement::new(ShaderDataType::Float3, "a_Position"),
+                BufferElement::new(ShaderDataType::Float4, "a_Color")
+            ]), MAX_LINES * 2, None);
+
+        let white_texture = Texture2D::from_rgba(&render_command, 1, 1, &[255; 4], TextureSettings::nearest())?;
+
+        Ok(Renderer2D {
+            camera_buffer: CameraBuffer::new(&render_command),
+            render_command,
+            quads,
+            circles,
+            lines,
+            texture_slots: vec![Rc::new(white_texture)],
+            line_width: 1.0,
+            stats: Renderer2DStats::default()
+        })
+    }
+
+    pub fn begin_scene(&mut self, camera: &dyn Camera) {
+        self.camera_buffer.set_camera(camera);
+        self.start_batch();
+    }
+
+    pub fn end_scene(&mut self) {
+        self.flush();
+    }
+
+    fn start_batch(&mut self) {
+        self.quads.vertices.clear();
+        self.circles.vertices.clear();
+        self.lines.vertices.clear();
+        self.texture_slots.truncate(1);
+    }
+
+    // Draws everything batched so far
+    pub fn flush(&mut self) {
+        self.render_command.set_blend_mode(BlendMode::Alpha);
+        // Other scenes may have bound their camera since begin_scene
+        self.camera_buffer.bind();
+
+        if self.quads.upload() {
+            for (slot, texture) in self.texture_slots.iter().enumerate() {
+                texture.bind(slot as u32);
+            }
+
+            let index_count = (self.quads.vertices.len() / 4 * 6) as u32;
+            self.render_command.draw_indexed(&self.quads.vertex_array, Some(index_count));
+            self.stats.draw_calls += 1;
+        }
+
+        if self.circles.upload() {
+            let index_count = (self.circles.vertices.len() / 4 * 6) as u32;
+            self.render_command.draw_indexed(&self.circles.vertex_array, Some(index_count));
+            self.stats.draw_calls += 1;
+        }
+
+        if self.lines.upload() {
+            self.render_command.set_line_width(self.line_width);
+            self.render_command.draw_lines(&self.lines.vertex_array, self.lines.vertices.len() as u32);
+            self.stats.draw_calls += 1;
+        }
+
+        self.start_batch();
+    }
+
+    // Primitives
+
+    pub fn draw_quad(&mut self, position: Vec3, size: Vec2, color: Vec4) {
+        self.draw_quad_transform(&transform(position, size, 0.0), color, None, 1.0);
+    }
+
+    // The rotation is in radians, counter clockwise
+    pub fn draw_rotated_quad(&mut self, position: Vec3, size: Vec2, rotation: f32, color: Vec4) {
+        self.draw_quad_transform(&transform(position, size, rotation), color, None, 1.0);
+    }
+
+    // The tiling repeats the texture that many times across the quad
+    pub fn draw_textured_quad(&mut self, position: Vec3, size: Vec2, texture: &Rc<Texture2D>, tiling: f32, tint: Vec4) {
+        self.draw_quad_transform(&transform(position, size, 0.0), tint, Some(texture), tiling);
+    }
+
+    pub fn draw_rotated_textured_quad(&mut self, position: Vec3, size: Vec2, rotation: f32, texture: &Rc<Texture2D>, tiling: f32, tint: Vec4) {
+        self.draw_quad_transform(&transform(position, size, rotation), tint, Some(texture), tiling);
+    }
+
+    // Quad of size 1 centered at the origin, moved by the transform
+    pub fn draw_quad_transform(&mut self, transform: &Mat4, color: Vec4, texture: Option<&Rc<Texture2D>>, tiling: f32) {
+        if self.quads.vertices.len() >= MAX_VERTICES || !self.circles.vertices.is_empty() || !self.lines.vertices.is_empty() {
+            self.flush();
+        }
+
+        let tex_index = match texture {
+            Some(texture) => self.get_texture_slot(texture),
+            None => 0,
+        } as f32;
+
+        for corner in 0..4 {
+            self.quads.vertices.push(QuadVertex {
+                position: transform.transform_point3(QUAD_POSITIONS[corner]).to_array(),
+                color: color.to_array(),
+                tex_coord: QUAD_TEX_COORDS[corner],
+                tex_index,
+                tiling
+            });
+        }
+
+        self.stats.quad_count += 1;
+    }
+
+    // Thickness goes from 0 to 1, where 1 fills the circle, fade softens the edges
+    pub fn draw_circle(&mut self, position: Vec3, radius: f32, color: Vec4, thickness: f32, fade: f32) {
+        self.draw_circle_transform(&transform(position, Vec2::splat(radius * 2.0), 0.0), color, thickness, fade);
+    }
+
+    pub fn draw_circle_transform(&mut self, transform: &Mat4, color: Vec4, thickness: f32, fade: f32) {
+        if self.circles.vertices.len() >= MAX_VERTICES || !self.quads.vertices.is_empty() || !self.lines.vertices.is_empty() {
+            self.flush();
+        }
+
+        for corner in QUAD_POSITIONS {
+            self.circles.vertices.push(CircleVertex {
+                world_position: transform.transform_point3(corner).to_array(),
+                local_position: (corner * 2.0).to_array(),
+                color: color.to_array(),
+                thickness,
+                fade: fade.max(0.0001)
+            });
+        }
+
+        self.stats.circle_count += 1;
+    }
+
+    pub fn draw_line(&mut self, start: Vec3, end: Vec3, color: Vec4) {
+        if self.lines.vertices.len() >= MAX_LINES * 2 || !self.quads.vertices.is_empty() || !self.circles.vertices.is_empty() {
+            self.flush();
+        }
+
+        self.lines.vertices.push(LineVertex { position: start.to_array(), color: color.to_array() });
+        self.lines.vertices.push(LineVertex { position: end.to_array(), color: color.to_array() });
+
+        self.stats.line_count += 1;
+    }
+
+    // Outline of a quad made of lines
+    pub fn draw_rect(&mut self, position: Vec3, size: Vec2, color: Vec4) {
+        let transform = transform(position, size, 0.0);
+        let corners = QUAD_POSITIONS.map(|corner| transform.transform_point3(corner));
+
+        for index in 0..4 {
+            self.draw_line(corners[index], corners[(index + 1) % 4], color);
+        }
+    }
+
+    // Width in pixels of the lines of the next flush
+    pub fn set_line_width(&mut self, width: f32) {
+        if width != self.line_width {
+            self.flush();
+            self.line_width = width;
+        }
+    }
+
+    pub fn get_line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    fn get_texture_slot(&mut self, texture: &Rc<Texture2D>) -> usize {
+        if let Some(slot) = self.texture_slots.iter().position(|other| **other == **texture) {
+            return slot;
+        }
+
+        // The batch is drawn when it runs out of slots, its quads keep their textures
+        if self.texture_slots.len() >= MAX_TEXTURE_SLOTS {
+            self.flush();
+        }
+
+        self.texture_slots.push(texture.clone());
+        self.texture_slots.len() - 1
+    }
+
+    // Statistics since the last reset, the application resets them every frame
+    pub fn get_stats(&self) -> Renderer2DStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = Renderer2DStats::default();
+    }
+}
+
+fn transform(position: Vec3, size: Vec2, rotation: f32) -> Mat4 {
+    Transform::from_2d(position, size, rotation).get_matrix()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::renderer::{HeadlessContext, OrthographicCamera};
+
+    use super::*;
+
+    #[test]
+    fn primitives_keep_their_order() {
+        let (_context, api) = match HeadlessContext::new(true, false) {
+            Ok(headless) => headless,
+            Err(err) => {
+                eprintln!("Skipping the 2D renderer: {}", err);
+                return;
+            },
+        };
+
+        let mut renderer = Renderer2D::new(Rc::new(api)).unwrap();
+        renderer.begin_scene(&OrthographicCamera::new(-1.0, 1.0, -1.0, 1.0));
+        renderer.draw_quad(Vec3::ZERO, Vec2::ONE, Vec4::ONE);
+        renderer.draw_quad(Vec3::ZERO, Vec2::ONE, Vec4::ONE);
+        renderer.draw_circle(Vec3::ZERO, 0.5, Vec4::ONE, 1.0, 0.0);
+        renderer.draw_quad(Vec3::ZERO, Vec2::ONE, Vec4::ONE);
+        renderer.end_scene();
+
+        // The quads before and after the circle can't share a draw call
+        assert_eq!(renderer.get_stats().draw_calls, 3);
+    }
+}
@@ -0,0 +1,54 @@
+use super::VertexArray;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlendMode {
+    None,
+    // Straight alpha: src * a + dst * (1 - a)
+    Alpha,
+    // Colors already multiplied by alpha: src + dst * (1 - a)
+    Premultiplied,
+    Additive
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DepthFunc {
+    Never,
+    Less,
+    Equal,
+    LessEqual,
+    Greater,
+    NotEqual,
+    GreaterEqual,
+    Always
+}
+
+// Commands that every graphics backend has to implement, the errors they
+// raise are reported with the place in the code that called them
+pub trait RendererAPI {
+    #[track_caller]
+    fn set_clear_color(&self, color: [f32; 4]);
+    #[track_caller]
+    fn clear(&self);
+
+    #[track_caller]
+    fn set_viewport(&self, x: i32, y: i32, width: u32, height: u32);
+    fn get_viewport(&self) -> (i32, i32, u32, u32);
+
+    // Draws triangles with the index buffer of the vertex array, None draws all its indices
+    #[track_caller]
+    fn draw_indexed(&self, vertex_array: &VertexArray, index_count: Option<u32>);
+    // Draws a line for each pair of vertices of the vertex array
+    #[track_caller]
+    fn draw_lines(&self, vertex_array: &VertexArray, vertex_count: u32);
+    #[track_caller]
+    fn set_line_width(&self, width: f32);
+
+    #[track_caller]
+    fn set_blend_mode(&self, mode: BlendMode);
+
+    // None disables the depth test
+    #[track_caller]
+    fn set_depth_test(&self, func: Option<DepthFunc>);
+    #[track_caller]
+    fn set_depth_write(&self, enabled: bool);
+}
@@ -0,0 +1,820 @@
+use std::{
+    cell::{Cell, Ref, RefCell},
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc
+};
+
+use glow::HasContext;
+
+use crate::{logger::*, math::{Mat3, Mat4, Vec2, Vec3, Vec4}};
+
+use super::{
+    uniform_buffer::{CAMERA_BINDING, CAMERA_BLOCK, MATERIAL_BINDING, MATERIAL_BLOCK},
+    RenderCommand
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Geometry,
+    Compute
+}
+
+impl ShaderStage {
+    // Name used after #type in the multi-stage files
+    pub fn from_name(name: &str) -> Option<ShaderStage> {
+        match name {
+            "vertex" => Some(ShaderStage::Vertex),
+            "fragment" | "pixel" => Some(ShaderStage::Fragment),
+            "geometry" => Some(ShaderStage::Geometry),
+            "compute" => Some(ShaderStage::Compute),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ShaderStage::Vertex => "vertex",
+            ShaderStage::Fragment => "fragment",
+            ShaderStage::Geometry => "geometry",
+            ShaderStage::Compute => "compute",
+        }
+    }
+
+    fn to_gl(self) -> u32 {
+        match self {
+            ShaderStage::Vertex => glow::VERTEX_SHADER,
+            ShaderStage::Fragment => glow::FRAGMENT_SHADER,
+            ShaderStage::Geometry => glow::GEOMETRY_SHADER,
+            ShaderStage::Compute => glow::COMPUTE_SHADER,
+        }
+    }
+}
+
+// Source of one stage, the first line is used to report errors with the lines of the file
+#[derive(Clone)]
+pub struct StageSource {
+    pub stage: ShaderStage,
+    pub source: String,
+    pub first_line: usize
+}
+
+impl StageSource {
+    pub fn new(stage: ShaderStage, source: &str) -> StageSource {
+        StageSource { stage, source: source.to_string(), first_line: 1 }
+    }
+}
+
+// Uniform or attribute found in the linked program
+#[derive(Clone, Debug)]
+pub struct ShaderResource {
+    pub name: String,
+    // GL type, like glow::FLOAT_VEC3 or glow::SAMPLER_2D
+    pub gl_type: u32,
+    // Number of elements for arrays
+    pub size: i32
+}
+
+impl ShaderResource {
+    pub fn get_type_name(&self) -> &'static str {
+        match self.gl_type {
+            glow::FLOAT => "float",
+            glow::FLOAT_VEC2 => "vec2",
+            glow::FLOAT_VEC3 => "vec3",
+            glow::FLOAT_VEC4 => "vec4",
+            glow::INT => "int",
+            glow::INT_VEC2 => "ivec2",
+            glow::INT_VEC3 => "ivec3",
+            glow::INT_VEC4 => "ivec4",
+            glow::UNSIGNED_INT => "uint",
+            glow::BOOL => "bool",
+            glow::FLOAT_MAT2 => "mat2",
+            glow::FLOAT_MAT3 => "mat3",
+            glow::FLOAT_MAT4 => "mat4",
+            glow::SAMPLER_2D => "sampler2D",
+            glow::SAMPLER_CUBE => "samplerCube",
+            glow::INT_SAMPLER_2D => "isampler2D",
+            _ => "unknown",
+        }
+    }
+}
+
+// Member of a uniform block, the offset follows the std140 rules
+#[derive(Clone, Debug)]
+pub struct BlockMember {
+    // Without the instance name of the block
+    pub name: String,
+    pub gl_type: u32,
+    // Number of elements for arrays
+    pub size: i32,
+    // Bytes from the start of the block
+    pub offset: usize
+}
+
+// Uniform block found in the linked program, it reads the buffer bound to its binding point
+#[derive(Clone, Debug)]
+pub struct UniformBlock {
+    pub name: String,
+    pub binding: u32,
+    // Bytes of the buffer it reads
+    pub size: usize,
+    // Empty when the block doesn't use the std140 layout
+    pub members: Vec<BlockMember>
+}
+
+impl UniformBlock {
+    pub fn get_member(&self, name: &str) -> Option<&BlockMember> {
+        self.members.iter().find(|member| member.name == name)
+    }
+}
+
+// Last value given to a uniform, applied again when a reload links a new program
+#[derive(Clone, Debug)]
+enum UniformValue {
+    Int(i32),
+    IntArray(Vec<i32>),
+    Float(f32),
+    Float2(Vec2),
+    Float3(Vec3),
+    Float4(Vec4),
+    Mat3(Mat3),
+    Mat4(Mat4)
+}
+
+pub struct Shader {
+    gl: Rc<glow::Context>,
+    name: String,
+    // Cells so a reload can replace the program of a shader that is shared
+    program: Cell<glow::Program>,
+    uniforms: RefCell<Vec<ShaderResource>>,
+    attributes: RefCell<Vec<ShaderResource>>,
+    blocks: RefCell<Vec<UniformBlock>>,
+    // None for the names that are not active, so they are only reported once
+    locations: RefCell<HashMap<String, Option<glow::UniformLocation>>>,
+    values: RefCell<HashMap<String, UniformValue>>,
+    path: Option<PathBuf>
+}
+
+impl Shader {
+    pub fn new(render_command: &RenderCommand, name: &str, vertex_source: &str, fragment_source: &str) -> Result<Shader, String> {
+        Self::from_stages(render_command, name, &[
+            StageSource::new(ShaderStage::Vertex, vertex_source),
+            StageSource::new(ShaderStage::Fragment, fragment_source)
+        ])
+    }
+
+    pub fn from_stages(render_command: &RenderCommand, name: &str, stages: &[StageSource]) -> Result<Shader, String> {
+        let gl = render_command.gl().clone();
+        let program = compile_program(&gl, name, stages)?;
+
+        let (uniforms, attributes) = reflect(&gl, program);
+        let blocks = reflect_blocks(&gl, program, name, stages);
+        hds_core_trace!("Shader '{}' linked with {} uniforms, {} attributes and {} uniform blocks", name, uniforms.len(), attributes.len(), blocks.len());
+
+        Ok(Shader {
+            gl,
+            name: name.to_string(),
+            program: Cell::new(program),
+            uniforms: RefCell::new(uniforms),
+            attributes: RefCell::new(attributes),
+            blocks: RefCell::new(blocks),
+            locations: RefCell::new(HashMap::new()),
+            values: RefCell::new(HashMap::new()),
+            path: None
+        })
+    }
+
+    // Source with the stages split by "#type vertex", "#type fragment"... lines
+    pub fn from_source(render_command: &RenderCommand, name: &str, source: &str) -> Result<Shader, String> {
+        let stages = parse_stages(source).map_err(|err| {
+            let message = format!("Shader '{}': {}", name, err);
+            hds_core_error!("{}", message);
+            message
+        })?;
+
+        Self::from_stages(render_command, name, &stages)
+    }
+
+    // Loads a file in the same format as from_source,
+    // the shader is named after the file
+    pub fn from_file(render_command: &RenderCommand, path: impl AsRef<Path>) -> Result<Shader, String> {
+        let path = path.as_ref();
+        let name = path.file_stem().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string());
+
+        let stages = read_stages(&name, path)?;
+        let mut shader = Self::from_stages(render_command, &name, &stages)?;
+        shader.path = Some(path.to_path_buf());
+        Ok(shader)
+    }
+
+    // Compiles the file again, the current program is kept if it fails
+    pub fn reload(&self) -> Result<(), String> {
+        let path = self.path.as_ref().ok_or_else(|| format!("Shader '{}' was not loaded from a file", self.name))?;
+
+        let stages = read_stages(&self.name, path)?;
+        let program = compile_program(&self.gl, &self.name, &stages)?;
+
+        let (uniforms, attributes) = reflect(&self.gl, program);
+        let mut blocks = reflect_blocks(&self.gl, program, &self.name, &stages);
+
+        // The bindings given with set_block_binding are kept
+        for block in blocks.iter_mut() {
+            let previous = self.blocks.borrow().iter().find(|other| other.name == block.name).map(|other| other.binding);
+
+            if let (Some(binding), Some(index)) = (previous, unsafe { self.gl.get_uniform_block_index(program, &block.name) }) {
+                unsafe { self.gl.uniform_block_binding(program, index, binding) };
+                block.binding = binding;
+            }
+        }
+
+        unsafe { self.gl.delete_program(self.program.replace(program)) };
+        *self.uniforms.borrow_mut() = uniforms;
+        *self.attributes.borrow_mut() = attributes;
+        *self.blocks.borrow_mut() = blocks;
+        self.locations.borrow_mut().clear();
+
+        // The new program starts with its uniforms at 0, like the samplers set once at startup
+        for (name, value) in self.values.borrow().iter() {
+            self.upload(name, value);
+        }
+
+        hds_core_info!("Reloaded shader '{}'", self.name);
+        Ok(())
+    }
+
+    pub fn bind(&self) {
+        unsafe { self.gl.use_program(Some(self.program.get())) };
+    }
+
+    pub fn unbind(&self) {
+        unsafe { self.gl.use_program(None) };
+    }
+
+    // Runs a compute shader with the given number of work groups
+    pub fn dispatch(&self, x: u32, y: u32, z: u32) {
+        self.bind();
+        unsafe { self.gl.dispatch_compute(x, y, z) };
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn get_uniforms(&self) -> Ref<'_, Vec<ShaderResource>> {
+        self.uniforms.borrow()
+    }
+
+    pub fn get_attributes(&self) -> Ref<'_, Vec<ShaderResource>> {
+        self.attributes.borrow()
+    }
+
+    pub fn get_uniform_blocks(&self) -> Ref<'_, Vec<UniformBlock>> {
+        self.blocks.borrow()
+    }
+
+    pub fn get_uniform_block(&self, name: &str) -> Option<Ref<'_, UniformBlock>> {
+        Ref::filter_map(self.blocks.borrow(), |blocks| blocks.iter().find(|block| block.name == name)).ok()
+    }
+
+    // The Camera and Material blocks are bound when the shader is linked, the others start at binding 0
+    pub fn set_block_binding(&self, name: &str, binding: u32) {
+        let program = self.program.get();
+
+        match unsafe { self.gl.get_uniform_block_index(program, name) } {
+            Some(index) => {
+                unsafe { self.gl.uniform_block_binding(program, index, binding) };
+
+                if let Some(block) = self.blocks.borrow_mut().iter_mut().find(|block| block.name == name) {
+                    block.binding = binding;
+                }
+            },
+            None => hds_core_warn!("Uniform block '{}' is not active in shader '{}'", name, self.name),
+        }
+    }
+
+    // Location of the attribute, used to match the vertex buffer layouts
+    pub fn get_attribute_location(&self, name: &str) -> Option<u32> {
+        unsafe { self.gl.get_attrib_location(self.program.get(), name) }
+    }
+
+    fn get_location(&self, name: &str) -> Option<glow::UniformLocation> {
+        if let Some(location) = self.locations.borrow().get(name) {
+            return *location;
+        }
+
+        let location = unsafe { self.gl.get_uniform_location(self.program.get(), name) };
+        if location.is_none() {
+            hds_core_warn!("Uniform '{}' is not active in shader '{}'", name, self.name);
+        }
+
+        self.locations.borrow_mut().insert(name.to_string(), location);
+        location
+    }
+
+    // The setters bind the program, GL can only write the uniforms of the bound one
+
+    pub fn set_int(&self, name: &str, value: i32) {
+        self.set_uniform(name, UniformValue::Int(value));
+    }
+
+    pub fn set_int_array(&self, name: &str, values: &[i32]) {
+        self.set_uniform(name, UniformValue::IntArray(values.to_vec()));
+    }
+
+    pub fn set_float(&self, name: &str, value: f32) {
+        self.set_uniform(name, UniformValue::Float(value));
+    }
+
+    pub fn set_float2(&self, name: &str, value: Vec2) {
+        self.set_uniform(name, UniformValue::Float2(value));
+    }
+
+    pub fn set_float3(&self, name: &str, value: Vec3) {
+        self.set_uniform(name, UniformValue::Float3(value));
+    }
+
+    pub fn set_float4(&self, name: &str, value: Vec4) {
+        self.set_uniform(name, UniformValue::Float4(value));
+    }
+
+    pub fn set_mat3(&self, name: &str, value: &Mat3) {
+        self.set_uniform(name, UniformValue::Mat3(*value));
+    }
+
+    pub fn set_mat4(&self, name: &str, value: &Mat4) {
+        self.set_uniform(name, UniformValue::Mat4(*value));
+    }
+
+    // Makes the sampler read the texture bound to the slot
+    pub fn set_texture_slot(&self, name: &str, slot: u32) {
+        self.set_int(name, slot as i32);
+    }
+
+    // Writes the uniform and keeps its value for the reloads
+    fn set_uniform(&self, name: &str, value: UniformValue) {
+        self.upload(name, &value);
+
+        let mut values = self.values.borrow_mut();
+        match values.get_mut(name) {
+            Some(previous) => *previous = value,
+            None => { values.insert(name.to_string(), value); },
+        }
+    }
+
+    fn upload(&self, name: &str, value: &UniformValue) {
+        self.bind();
+        let location = self.get_location(name);
+        let location = location.as_ref();
+
+        unsafe {
+            match value {
+                UniformValue::Int(value) => self.gl.uniform_1_i32(location, *value),
+                UniformValue::IntArray(values) => self.gl.uniform_1_i32_slice(location, values),
+                UniformValue::Float(value) => self.gl.uniform_1_f32(location, *value),
+                UniformValue::Float2(value) => self.gl.uniform_2_f32_slice(location, &value.to_array()),
+                UniformValue::Float3(value) => self.gl.uniform_3_f32_slice(location, &value.to_array()),
+                UniformValue::Float4(value) => self.gl.uniform_4_f32_slice(location, &value.to_array()),
+                UniformValue::Mat3(value) => self.gl.uniform_matrix_3_f32_slice(location, false, &value.to_cols_array()),
+                UniformValue::Mat4(value) => self.gl.uniform_matrix_4_f32_slice(location, false, &value.to_cols_array()),
+            }
+        }
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_program(self.program.get()) };
+    }
+}
+
+fn read_stages(name: &str, path: &Path) -> Result<Vec<StageSource>, String> {
+    let source = fs::read_to_string(path).map_err(|err| {
+        let message = format!("Failed to read shader '{}': {}", path.display(), err);
+        hds_core_error!("{}", message);
+        message
+    })?;
+
+    parse_stages(&source).map_err(|err| {
+        let message = format!("Shader '{}': {}", name, err);
+        hds_core_error!("{}", message);
+        message
+    })
+}
+
+// Splits a multi-stage source in its "#type <stage>" sections
+pub fn parse_stages(source: &str) -> Result<Vec<StageSource>, String> {
+    let mut stages: Vec<StageSource> = vec![];
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+
+        if let Some(stage_name) = line.trim().strip_prefix("#type") {
+            let stage = ShaderStage::from_name(stage_name.trim())
+                .ok_or_else(|| format!("Unknown shader stage '{}' at line {}", stage_name.trim(), line_number))?;
+
+            if stages.iter().any(|other| other.stage == stage) {
+                return Err(format!("Shader stage '{}' repeated at line {}", stage.get_name(), line_number));
+            }
+
+            stages.push(StageSource { stage, source: String::new(), first_line: line_number + 1 });
+            continue;
+        }
+
+        match stages.last_mut() {
+            Some(current) => {
+                current.source.push_str(line);
+                current.source.push('\n');
+            },
+            None if line.trim().is_empty() => {},
+            None => return Err(format!("Code before the first #type line at line {}", line_number)),
+        }
+    }
+
+    if stages.is_empty() {
+        return Err("No #type sections found".to_string());
+    }
+
+    Ok(stages)
+}
+
+fn compile_program(gl: &glow::Context, name: &str, stages: &[StageSource]) -> Result<glow::Program, String> {
+    unsafe {
+        let program = gl.create_program().map_err(|err| format!("Failed to create program for shader '{}': {}", name, err))?;
+        let mut shaders = Vec::with_capacity(stages.len());
+
+        let mut result = Ok(());
+        for stage in stages {
+            match compile_stage(gl, name, stage) {
+                Ok(shader) => {
+                    gl.attach_shader(program, shader);
+                    shaders.push(shader);
+                },
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                },
+            }
+        }
+
+        if result.is_ok() {
+            gl.link_program(program);
+
+            if !gl.get_program_link_status(program) {
+                let message = format!("Failed to link shader '{}':\n{}", name, gl.get_program_info_log(program).trim_end());
+                hds_core_error!("{}", message);
+                result = Err(message);
+            }
+        }
+
+        // The program keeps the compiled code once linked
+        for shader in shaders {
+            gl.detach_shader(program, shader);
+            gl.delete_shader(shader);
+        }
+
+        match result {
+            Ok(()) => Ok(program),
+            Err(err) => {
+                gl.delete_program(program);
+                Err(err)
+            },
+        }
+    }
+}
+
+fn compile_stage(gl: &glow::Context, name: &str, stage: &StageSource) -> Result<glow::Shader, String> {
+    unsafe {
+        let shader = gl.create_shader(stage.stage.to_gl())
+            .map_err(|err| format!("Failed to create {} shader for '{}': {}", stage.stage.get_name(), name, err))?;
+
+        gl.shader_source(shader, &stage.source);
+        gl.compile_shader(shader);
+
+        if gl.get_shader_compile_status(shader) {
+            return Ok(shader);
+        }
+
+        let log = annotate_log(&gl.get_shader_info_log(shader), stage);
+        gl.delete_shader(shader);
+
+        let message = format!("Failed to compile the {} stage of shader '{}':\n{}", stage.stage.get_name(), name, log);
+        hds_core_error!("{}", message);
+        Err(message)
+    }
+}
+
+// Adds the line of the file and its code to each message of the info log
+fn annotate_log(log: &str, stage: &StageSource) -> String {
+    let lines: Vec<&str> = stage.source.lines().collect();
+    let mut annotated = String::new();
+
+    for message in log.lines().filter(|message| !message.trim().is_empty()) {
+        match error_line(message).filter(|line| *line >= 1 && *line <= lines.len()) {
+            Some(line) => {
+                let _ = writeln!(annotated, "line {}: {}", line + stage.first_line - 1, message.trim());
+                let _ = writeln!(annotated, "    | {}", lines[line - 1].trim());
+            },
+            None => { let _ = writeln!(annotated, "{}", message.trim()); },
+        }
+    }
+
+    annotated.trim_end().to_string()
+}
+
+// Line number of a driver message, the common formats are
+// "0:12(5): error" (Mesa), "0(12) : error" (NVIDIA) and "ERROR: 0:12: ..." (AMD, Intel)
+fn error_line(message: &str) -> Option<usize> {
+    let rest = message.trim().trim_start_matches("ERROR:").trim_start_matches("WARNING:").trim_start();
+    let rest = rest.strip_prefix("0:").or_else(|| rest.strip_prefix("0("))?;
+
+    let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+fn reflect(gl: &glow::Context, program: glow::Program) -> (Vec<ShaderResource>, Vec<ShaderResource>) {
+    unsafe {
+        let uniforms = (0..gl.get_active_uniforms(program))
+            .filter_map(|index| gl.get_active_uniform(program, index))
+            .map(|uniform| ShaderResource { name: uniform.name, gl_type: uniform.utype, size: uniform.size })
+            .collect();
+
+        let attributes = (0..gl.get_active_attributes(program))
+            .filter_map(|index| gl.get_active_attribute(program, index))
+            .map(|attribute| ShaderResource { name: attribute.name, gl_type: attribute.atype, size: attribute.size })
+            .collect();
+
+        (uniforms, attributes)
+    }
+}
+
+// Uniform blocks of the program, found in the sources since glGetActiveUniformsiv is not
+// available to read the offsets of the members. The std140 layout is computed from the
+// order of the members in the source and checked against the size GL reports
+fn reflect_blocks(gl: &glow::Context, program: glow::Program, name: &str, stages: &[StageSource]) -> Vec<UniformBlock> {
+    let mut blocks: Vec<UniformBlock> = vec![];
+
+    for declaration in stages.iter().flat_map(|stage| find_block_declarations(&stage.source)) {
+        if blocks.iter().any(|block| block.name == declaration.name) {
+            continue;
+        }
+
+        // Blocks that are declared but not used are removed by the linker
+        let index = match unsafe { gl.get_uniform_block_index(program, &declaration.name) } {
+            Some(index) => index,
+            None => continue,
+        };
+
+        let binding = match declaration.name.as_str() {
+            CAMERA_BLOCK => Some(CAMERA_BINDING),
+            MATERIAL_BLOCK => Some(MATERIAL_BINDING),
+            _ => None,
+        };
+
+        unsafe {
+            if let Some(binding) = binding {
+                gl.uniform_block_binding(program, index, binding);
+            }
+
+            let binding = gl.get_active_uniform_block_parameter_i32(program, index, glow::UNIFORM_BLOCK_BINDING) as u32;
+            let size = gl.get_active_uniform_block_parameter_i32(program, index, glow::UNIFORM_BLOCK_DATA_SIZE) as usize;
+
+            let count = gl.get_active_uniform_block_parameter_i32(program, index, glow::UNIFORM_BLOCK_ACTIVE_UNIFORMS) as usize;
+            let mut indices = vec![0; count];
+            gl.get_active_uniform_block_parameter_i32_slice(program, index, glow::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES, &mut indices);
+
+            let uniforms: Vec<ShaderResource> = indices.iter()
+                .filter_map(|uniform| gl.get_active_uniform(program, *uniform as u32))
+                .map(|uniform| ShaderResource { name: uniform.name, gl_type: uniform.utype, size: uniform.size })
+                .collect();
+
+            let members = std140_members(&declaration, &uniforms, size).unwrap_or_else(|err| {
+                hds_core_warn!("Uniform block '{}' of shader '{}': {}", declaration.name, name, err);
+                vec![]
+            });
+
+            blocks.push(UniformBlock { name: declaration.name, binding, size, members });
+        }
+    }
+
+    blocks
+}
+
+struct BlockDeclaration {
+    name: String,
+    // Text between the braces
+    body: String,
+    std140: bool
+}
+
+// Finds the "uniform <Name> { ... }" declarations of a stage
+fn find_block_declarations(source: &str) -> Vec<BlockDeclaration> {
+    let source = strip_comments(source);
+    let mut declarations = vec![];
+
+    for (start, _) in source.match_indices("uniform") {
+        let before = &source[..start];
+        let after = &source[start + "uniform".len()..];
+
+        if before.ends_with(is_identifier_char) || !after.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        let after = after.trim_start();
+        let name: String = after.chars().take_while(|c| is_identifier_char(*c)).collect();
+        let rest = after[name.len()..].trim_start();
+
+        let body = match rest.strip_prefix('{').and_then(|rest| rest.split_once('}')) {
+            Some((body, _)) if !name.is_empty() => body,
+            _ => continue,
+        };
+
+        // Qualifiers since the end of the previous statement
+        let qualifiers = &before[before.rfind([';', '}']).map_or(0, |index| index + 1)..];
+        declarations.push(BlockDeclaration { name, body: body.to_string(), std140: qualifiers.contains("std140") });
+    }
+
+    declarations
+}
+
+// Source without the // and /* */ comments, each one becomes a space and the line breaks are kept
+fn strip_comments(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.next_if(|next| *next != '\n').is_some() {}
+                stripped.push(' ');
+            },
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    // Line breaks are kept for the line numbers
+                    if next == '\n' {
+                        stripped.push('\n');
+                    }
+                    previous = next;
+                }
+                stripped.push(' ');
+            },
+            _ => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn std140_members(declaration: &BlockDeclaration, uniforms: &[ShaderResource], size: usize) -> Result<Vec<BlockMember>, String> {
+    if !declaration.std140 {
+        return Err("it is not declared with layout(std140), its members can't be written".to_string());
+    }
+
+    let mut members = vec![];
+    for uniform in uniforms {
+        // Blocks with an instance name prefix their members with the block name
+        let name = uniform.name.strip_prefix(&format!("{}.", declaration.name)).unwrap_or(&uniform.name);
+        let name = name.strip_suffix("[0]").unwrap_or(name);
+
+        let position = find_word(&declaration.body, name).ok_or_else(|| format!("member '{}' is not supported", name))?;
+        members.push((position, BlockMember { name: name.to_string(), gl_type: uniform.gl_type, size: uniform.size, offset: 0 }));
+    }
+
+    members.sort_by_key(|(position, _)| *position);
+
+    let mut offset: usize = 0;
+    for (_, member) in members.iter_mut() {
+        let (alignment, member_size) = std140_size(member.gl_type)
+            .ok_or_else(|| format!("member '{}' has a type that is not supported", member.name))?;
+
+        // The elements of the arrays are aligned to vec4
+        let (alignment, member_size) = match member.size {
+            1 => (alignment, member_size),
+            count => (16, member_size.next_multiple_of(16) * count as usize),
+        };
+
+        member.offset = offset.next_multiple_of(alignment);
+        offset = member.offset + member_size;
+    }
+
+    if offset.next_multiple_of(16) != size.next_multiple_of(16) {
+        return Err(format!("its std140 size of {} bytes doesn't match the {} bytes of GL", offset, size));
+    }
+
+    Ok(members.into_iter().map(|(_, member)| member).collect())
+}
+
+// Alignment and size in bytes of a type in the std140 layout
+fn std140_size(gl_type: u32) -> Option<(usize, usize)> {
+    match gl_type {
+        glow::FLOAT | glow::INT | glow::UNSIGNED_INT | glow::BOOL => Some((4, 4)),
+        glow::FLOAT_VEC2 | glow::INT_VEC2 | glow::UNSIGNED_INT_VEC2 | glow::BOOL_VEC2 => Some((8, 8)),
+        glow::FLOAT_VEC3 | glow::INT_VEC3 | glow::UNSIGNED_INT_VEC3 | glow::BOOL_VEC3 => Some((16, 12)),
+        glow::FLOAT_VEC4 | glow::INT_VEC4 | glow::UNSIGNED_INT_VEC4 | glow::BOOL_VEC4 => Some((16, 16)),
+        // Each column is a vec4
+        glow::FLOAT_MAT2 => Some((16, 32)),
+        glow::FLOAT_MAT3 => Some((16, 48)),
+        glow::FLOAT_MAT4 => Some((16, 64)),
+        _ => None,
+    }
+}
+
+// Position of the name as a whole word
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    text.match_indices(word).map(|(index, _)| index).find(|index| {
+        !text[..*index].ends_with(is_identifier_char) && !text[index + word.len()..].starts_with(is_identifier_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stages_splits_sections() {
+        let stages = parse_stages("\n#type vertex\nvoid main() {}\n#type pixel\nout vec4 color;\nvoid main() {}\n").unwrap();
+
+        assert_eq!(stages.len(), 2);
+        assert_eq!(stages[0].stage, ShaderStage::Vertex);
+        assert_eq!(stages[0].source, "void main() {}\n");
+        assert_eq!(stages[0].first_line, 3);
+        assert_eq!(stages[1].stage, ShaderStage::Fragment);
+        assert_eq!(stages[1].first_line, 5);
+    }
+
+    #[test]
+    fn parse_stages_errors() {
+        assert!(parse_stages("").is_err());
+        assert!(parse_stages("void main() {}\n#type vertex\n").is_err());
+        assert!(parse_stages("#type tessellation\n").is_err());
+        assert!(parse_stages("#type vertex\n#type vertex\n").is_err());
+    }
+
+    #[test]
+    fn find_block_declarations_skips_comments() {
+        let source = "
+            // uniform Commented { float a; };
+            /* uniform Block
+               { float b; }; */
+            uniform sampler2D u_Texture;
+            layout(std140) uniform Material { vec4 u_Color; };
+            uniform Plain { float u_Value; } plain;
+        ";
+
+        let declarations = find_block_declarations(source);
+        let names: Vec<&str> = declarations.iter().map(|declaration| declaration.name.as_str()).collect();
+
+        assert_eq!(names, ["Material", "Plain"]);
+        assert!(declarations[0].std140);
+        assert!(!declarations[1].std140);
+        assert_eq!(strip_comments("a /* b\nc */ d // e\nf").lines().count(), 3);
+    }
+
+    fn uniform(name: &str, gl_type: u32, size: i32) -> ShaderResource {
+        ShaderResource { name: name.to_string(), gl_type, size }
+    }
+
+    #[test]
+    fn std140_offsets() {
+        let declarations = find_block_declarations(
+            "layout(std140) uniform Material { vec3 u_Color; float u_Roughness; vec2 u_Offset; mat4 u_Transform; float u_Weights[3]; } material;"
+        );
+
+        // GL lists the members in any order, with the instance and the array index in their names
+        let uniforms = [
+            uniform("Material.u_Weights[0]", glow::FLOAT, 3),
+            uniform("Material.u_Transform", glow::FLOAT_MAT4, 1),
+            uniform("Material.u_Color", glow::FLOAT_VEC3, 1),
+            uniform("Material.u_Offset", glow::FLOAT_VEC2, 1),
+            uniform("Material.u_Roughness", glow::FLOAT, 1)
+        ];
+
+        let members = std140_members(&declarations[0], &uniforms, 144).unwrap();
+        let offsets: Vec<(&str, usize)> = members.iter().map(|member| (member.name.as_str(), member.offset)).collect();
+
+        assert_eq!(offsets, [("u_Color", 0), ("u_Roughness", 12), ("u_Offset", 16), ("u_Transform", 32), ("u_Weights", 96)]);
+        assert!(std140_members(&declarations[0], &uniforms, 160).is_err());
+    }
+
+    #[test]
+    fn std140_requires_the_layout() {
+        let declarations = find_block_declarations("uniform Material { float u_Value; };");
+        assert!(std140_members(&declarations[0], &[uniform("u_Value", glow::FLOAT, 1)], 16).is_err());
+    }
+}
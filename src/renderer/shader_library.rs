@@ -0,0 +1,130 @@
+use std::{
+    fs,
+    path::Path,
+    rc::Rc,
+    time::{Duration, Instant, SystemTime}
+};
+
+use crate::logger::*;
+
+use super::{RenderCommand, Shader};
+
+// Time between two checks of the shader files
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+struct ShaderEntry {
+    shader: Rc<Shader>,
+    modified: Option<SystemTime>,
+    // Error of the last reload, the shader keeps its last good program meanwhile
+    error: Option<String>
+}
+
+// Shaders of the application by name, the ones loaded from files are
+// compiled again when the files change
+pub struct ShaderLibrary {
+    render_command: RenderCommand,
+    shaders: Vec<ShaderEntry>,
+    hot_reload: bool,
+    last_poll: Instant
+}
+
+impl ShaderLibrary {
+    pub fn new(render_command: RenderCommand) -> ShaderLibrary {
+        ShaderLibrary { render_command, shaders: vec![], hot_reload: true, last_poll: Instant::now() }
+    }
+
+    // Replaces the shader with the same name
+    pub fn add(&mut self, shader: Rc<Shader>) {
+        let modified = shader.get_path().and_then(modified_time);
+        let entry = ShaderEntry { shader, modified, error: None };
+
+        match self.shaders.iter_mut().find(|other| other.shader.get_name() == entry.shader.get_name()) {
+            Some(other) => *other = entry,
+            None => self.shaders.push(entry),
+        }
+    }
+
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<Rc<Shader>, String> {
+        let shader = Rc::new(Shader::from_file(&self.render_command, path)?);
+        self.add(shader.clone());
+        Ok(shader)
+    }
+
+    // Shader of the file, only compiled the first time it is asked for
+    pub fn get_or_load(&mut self, path: impl AsRef<Path>) -> Result<Rc<Shader>, String> {
+        let key = path.as_ref().canonicalize().unwrap_or_else(|_| path.as_ref().to_path_buf());
+
+        let loaded = self.shaders.iter().find(|entry| {
+            entry.shader.get_path().is_some_and(|other| other.canonicalize().unwrap_or_else(|_| other.to_path_buf()) == key)
+        });
+
+        match loaded {
+            Some(entry) => Ok(entry.shader.clone()),
+            None => self.load(path),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Rc<Shader>> {
+        self.shaders.iter().find(|entry| entry.shader.get_name() == name).map(|entry| entry.shader.clone())
+    }
+
+    pub fn get_names(&self) -> Vec<&str> {
+        self.shaders.iter().map(|entry| entry.shader.get_name()).collect()
+    }
+
+    pub fn set_hot_reload(&mut self, enabled: bool) {
+        self.hot_reload = enabled;
+    }
+
+    pub fn is_hot_reload(&self) -> bool {
+        self.hot_reload
+    }
+
+    // Shaders whose last reload failed, with the error
+    pub fn get_errors(&self) -> Vec<(&str, &str)> {
+        self.shaders.iter()
+            .filter_map(|entry| entry.error.as_deref().map(|error| (entry.shader.get_name(), error)))
+            .collect()
+    }
+
+    // Called once per frame, reloads the shaders whose files changed since the last check
+    pub fn check_for_changes(&mut self) {
+        if !self.hot_reload || self.last_poll.elapsed() < POLL_INTERVAL {
+            return;
+        }
+        self.last_poll = Instant::now();
+
+        for entry in self.shaders.iter_mut() {
+            let modified = match entry.shader.get_path().and_then(modified_time) {
+                Some(modified) => modified,
+                None => continue,
+            };
+
+            if entry.modified == Some(modified) {
+                continue;
+            }
+
+            entry.modified = Some(modified);
+            entry.error = entry.shader.reload().err();
+        }
+    }
+
+    // Reloads every shader loaded from a file, returns the number of failures
+    pub fn reload_all(&mut self) -> usize {
+        for entry in self.shaders.iter_mut().filter(|entry| entry.shader.get_path().is_some()) {
+            entry.modified = entry.shader.get_path().and_then(modified_time);
+            entry.error = entry.shader.reload().err();
+        }
+
+        let failed = self.shaders.iter().filter(|entry| entry.error.is_some()).count();
+        if failed > 0 {
+            hds_core_warn!("{} shaders failed to reload", failed);
+        }
+
+        failed
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,52 @@
+#type vertex
+#version 330 core
+
+layout(location = 0) in vec3 a_WorldPosition;
+layout(location = 1) in vec3 a_LocalPosition;
+layout(location = 2) in vec4 a_Color;
+layout(location = 3) in float a_Thickness;
+layout(location = 4) in float a_Fade;
+
+layout(std140) uniform Camera
+{
+    mat4 u_ViewProjection;
+    vec4 u_CameraPosition;
+};
+
+out vec3 v_LocalPosition;
+out vec4 v_Color;
+out float v_Thickness;
+out float v_Fade;
+
+void main()
+{
+    v_LocalPosition = a_LocalPosition;
+    v_Color = a_Color;
+    v_Thickness = a_Thickness;
+    v_Fade = a_Fade;
+    gl_Position = u_ViewProjection * vec4(a_WorldPosition, 1.0);
+}
+
+#type fragment
+#version 330 core
+
+in vec3 v_LocalPosition;
+in vec4 v_Color;
+in float v_Thickness;
+in float v_Fade;
+
+out vec4 o_Color;
+
+void main()
+{
+    // The quad goes from -1 to 1, the circle is the part at distance 1 or less
+    float distance = 1.0 - length(v_LocalPosition);
+    float circle = smoothstep(0.0, v_Fade, distance);
+    circle *= smoothstep(v_Thickness + v_Fade, v_Thickness, distance);
+
+    if (circle == 0.0)
+        discard;
+
+    o_Color = v_Color;
+    o_Color.a *= circle;
+}
@@ -0,0 +1,31 @@
+#type vertex
+#version 330 core
+
+layout(location = 0) in vec3 a_Position;
+layout(location = 1) in vec4 a_Color;
+
+layout(std140) uniform Camera
+{
+    mat4 u_ViewProjection;
+    vec4 u_CameraPosition;
+};
+
+out vec4 v_Color;
+
+void main()
+{
+    v_Color = a_Color;
+    gl_Position = u_ViewProjection * vec4(a_Position, 1.0);
+}
+
+#type fragment
+#version 330 core
+
+in vec4 v_Color;
+
+out vec4 o_Color;
+
+void main()
+{
+    o_Color = v_Color;
+}
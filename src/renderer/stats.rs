@@ -0,0 +1,74 @@
+use std::cell::Cell;
+
+// Work sent to the GPU during one frame
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct RenderStats {
+    pub draw_calls: u32,
+    // Vertices processed by the draws, the indices for the indexed draws
+    pub vertices: u64,
+    // Pipeline state that was really sent to GL, like blending, depth or framebuffers
+    pub state_changes: u32,
+    pub texture_binds: u32,
+    pub buffer_uploads: u32,
+    pub texture_uploads: u32,
+    // Bytes of the buffer and texture uploads
+    pub uploaded_bytes: u64
+}
+
+// Counters of the frame being drawn, shared by the renderer and the GL objects it creates
+#[derive(Default)]
+pub struct StatsCounter {
+    current: Cell<RenderStats>,
+    last_frame: Cell<RenderStats>
+}
+
+impl StatsCounter {
+    pub fn add_draw_call(&self, vertices: u64) {
+        self.update(|stats| {
+            stats.draw_calls += 1;
+            stats.vertices += vertices;
+        });
+    }
+
+    pub fn add_state_change(&self) {
+        self.update(|stats| stats.state_changes += 1);
+    }
+
+    pub fn add_texture_bind(&self) {
+        self.update(|stats| stats.texture_binds += 1);
+    }
+
+    pub fn add_buffer_upload(&self, bytes: usize) {
+        self.update(|stats| {
+            stats.buffer_uploads += 1;
+            stats.uploaded_bytes += bytes as u64;
+        });
+    }
+
+    pub fn add_texture_upload(&self, bytes: usize) {
+        self.update(|stats| {
+            stats.texture_uploads += 1;
+            stats.uploaded_bytes += bytes as u64;
+        });
+    }
+
+    // Keeps the counts of the frame that ended and starts counting again
+    pub fn end_frame(&self) {
+        self.last_frame.set(self.current.take());
+    }
+
+    pub fn get_last_frame(&self) -> RenderStats {
+        self.last_frame.get()
+    }
+
+    // Counts of the frame being drawn so far
+    pub fn get_current(&self) -> RenderStats {
+        self.current.get()
+    }
+
+    fn update(&self, change: impl FnOnce(&mut RenderStats)) {
+        let mut stats = self.current.get();
+        change(&mut stats);
+        self.current.set(stats);
+    }
+}
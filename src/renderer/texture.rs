@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc
+};
+
+use glow::HasContext;
+
+use crate::logger::*;
+
+use super::{RenderCommand, StatsCounter};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextureFilter {
+    Nearest,
+    Linear
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextureWrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder
+}
+
+impl TextureWrap {
+    fn to_gl(self) -> i32 {
+        (match self {
+            TextureWrap::Repeat => glow::REPEAT,
+            TextureWrap::MirroredRepeat => glow::MIRRORED_REPEAT,
+            TextureWrap::ClampToEdge => glow::CLAMP_TO_EDGE,
+            TextureWrap::ClampToBorder => glow::CLAMP_TO_BORDER,
+        }) as i32
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TextureSettings {
+    pub min_filter: TextureFilter,
+    pub mag_filter: TextureFilter,
+    pub wrap_s: TextureWrap,
+    pub wrap_t: TextureWrap,
+    pub mipmaps: bool,
+    // Decodes the sRGB colors to linear when sampling. Only for renderers that write to sRGB
+    // framebuffers with GL_FRAMEBUFFER_SRGB, the default targets store the colors as they are
+    pub srgb: bool
+}
+
+impl Default for TextureSettings {
+    fn default() -> TextureSettings {
+        TextureSettings {
+            min_filter: TextureFilter::Linear,
+            mag_filter: TextureFilter::Linear,
+            wrap_s: TextureWrap::Repeat,
+            wrap_t: TextureWrap::Repeat,
+            mipmaps: true,
+            srgb: false
+        }
+    }
+}
+
+impl TextureSettings {
+    // Settings for pixel art and data textures: no filtering, no mipmaps
+    pub fn nearest() -> TextureSettings {
+        TextureSettings {
+            min_filter: TextureFilter::Nearest,
+            mag_filter: TextureFilter::Nearest,
+            mipmaps: false,
+            ..TextureSettings::default()
+        }
+    }
+}
+
+// RGBA texture, the first row of the data is the top of the image like in imgui
+pub struct Texture2D {
+    gl: Rc<glow::Context>,
+    stats: Rc<StatsCounter>,
+    texture: glow::Texture,
+    width: u32,
+    height: u32,
+    settings: TextureSettings,
+    path: Option<PathBuf>
+}
+
+impl Texture2D {
+    // Loads a PNG, JPEG or TGA image
+    pub fn from_file(render_command: &RenderCommand, path: impl AsRef<Path>, settings: TextureSettings) -> Result<Texture2D, String> {
+        let path = path.as_ref();
+
+        let image = image::open(path).map_err(|err| {
+            let message = format!("Failed to load texture '{}': {}", path.display(), err);
+            hds_core_error!("{}", message);
+            message
+        })?.into_rgba8();
+
+        let mut texture = Self::from_rgba(render_command, image.width(), image.height(), image.as_raw(), settings)?;
+        texture.path = Some(path.to_path_buf());
+
+        hds_core_trace!("Loaded texture '{}' ({}x{})", path.display(), texture.width, texture.height);
+        Ok(texture)
+    }
+
+    // Pixels with 4 bytes each, row by row
+    pub fn from_rgba(render_command: &RenderCommand, width: u32, height: u32, data: &[u8], settings: TextureSettings) -> Result<Texture2D, String> {
+        let texture = Self::new(render_command, width, height, settings)?;
+        texture.set_data(data)?;
+        Ok(texture)
+    }
+
+    // Texture without data, filled later with set_data or drawn to
+    pub fn new(render_command: &RenderCommand, width: u32, height: u32, settings: TextureSettings) -> Result<Texture2D, String> {
+        let gl = render_command.gl().clone();
+        let internal_format = if settings.srgb { glow::SRGB8_ALPHA8 } else { glow::RGBA8 };
+
+        let texture = unsafe {
+            let texture = gl.create_texture().map_err(|err| format!("Failed to create texture: {}", err))?;
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, min_filter(&settings));
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, match settings.mag_filter {
+                TextureFilter::Nearest => glow::NEAREST,
+                TextureFilter::Linear => glow::LINEAR,
+            } as i32);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, settings.wrap_s.to_gl());
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, settings.wrap_t.to_gl());
+
+            gl.tex_image_2d(glow::TEXTURE_2D, 0, internal_format as i32, width as i32, height as i32, 0,
+                glow::RGBA, glow::UNSIGNED_BYTE, None);
+            texture
+        };
+
+        Ok(Texture2D { gl, stats: render_command.get_stats_counter().clone(), texture, width, height, settings, path: None })
+    }
+
+    // Replaces all the pixels, the size must match the texture
+    pub fn set_data(&self, data: &[u8]) -> Result<(), String> {
+        let expected = self.width as usize * self.height as usize * 4;
+        if data.len() != expected {
+            let message = format!("Texture data has {} bytes, expected {} for {}x{} RGBA", data.len(), expected, self.width, self.height);
+            hds_core_error!("{}", message);
+            return Err(message);
+        }
+
+        unsafe {
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+            // Rows are tightly packed, not aligned to 4 bytes
+            self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            self.gl.tex_sub_image_2d(glow::TEXTURE_2D, 0, 0, 0, self.width as i32, self.height as i32,
+                glow::RGBA, glow::UNSIGNED_BYTE, glow::PixelUnpackData::Slice(data));
+            self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+
+            if self.settings.mipmaps {
+                self.gl.generate_mipmap(glow::TEXTURE_2D);
+            }
+        }
+
+        self.stats.add_texture_upload(data.len());
+
+        Ok(())
+    }
+
+    pub fn bind(&self, slot: u32) {
+        unsafe {
+            self.gl.active_texture(glow::TEXTURE0 + slot);
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+        }
+
+        self.stats.add_texture_bind();
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_settings(&self) -> &TextureSettings {
+        &self.settings
+    }
+
+    pub fn get_path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    // Id to draw the texture with imgui, the imgui layer maps the ids to the GL names
+    pub fn get_imgui_id(&self) -> imgui::TextureId {
+        imgui::TextureId::new(self.texture.0.get() as usize)
+    }
+}
+
+impl PartialEq for Texture2D {
+    fn eq(&self, other: &Texture2D) -> bool {
+        self.texture == other.texture
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_texture(self.texture) };
+    }
+}
+
+fn min_filter(settings: &TextureSettings) -> i32 {
+    (match (settings.min_filter, settings.mipmaps) {
+        (TextureFilter::Nearest, false) => glow::NEAREST,
+        (TextureFilter::Linear, false) => glow::LINEAR,
+        (TextureFilter::Nearest, true) => glow::NEAREST_MIPMAP_NEAREST,
+        (TextureFilter::Linear, true) => glow::LINEAR_MIPMAP_LINEAR,
+    }) as i32
+}
+
+// Textures loaded from files, each file is only loaded once
+pub struct TextureLibrary {
+    render_command: RenderCommand,
+    textures: HashMap<PathBuf, Rc<Texture2D>>
+}
+
+impl TextureLibrary {
+    pub fn new(render_command: RenderCommand) -> TextureLibrary {
+        TextureLibrary { render_command, textures: HashMap::new() }
+    }
+
+    // The settings are only used the first time the file is loaded
+    pub fn load(&mut self, path: impl AsRef<Path>, settings: TextureSettings) -> Result<Rc<Texture2D>, String> {
+        let key = Self::key(path.as_ref());
+
+        if let Some(texture) = self.textures.get(&key) {
+            return Ok(texture.clone());
+        }
+
+        let texture = Rc::new(Texture2D::from_file(&self.render_command, path, settings)?);
+        self.textures.insert(key, texture.clone());
+        Ok(texture)
+    }
+
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<Rc<Texture2D>> {
+        self.textures.get(&Self::key(path.as_ref())).cloned()
+    }
+
+    pub fn get_textures(&self) -> impl Iterator<Item = &Rc<Texture2D>> {
+        self.textures.values()
+    }
+
+    // Frees the textures that are only referenced by the library
+    pub fn remove_unused(&mut self) {
+        self.textures.retain(|_, texture| Rc::strong_count(texture) > 1);
+    }
+
+    // The same file can be written with different paths, like "a/../b.png" and "b.png"
+    fn key(path: &Path) -> PathBuf {
+        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+    }
+}
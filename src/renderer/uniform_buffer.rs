@@ -0,0 +1,113 @@
+use std::{mem, rc::Rc};
+
+use bytemuck::{Pod, Zeroable};
+use glow::HasContext;
+
+use crate::logger::*;
+
+use super::{buffer::as_bytes, Camera, RenderCommand, StatsCounter};
+
+// Blocks the shaders are linked to a fixed binding point, so the buffers
+// bound there are seen by every shader that declares them
+pub const CAMERA_BLOCK: &str = "Camera";
+pub const CAMERA_BINDING: u32 = 0;
+pub const MATERIAL_BLOCK: &str = "Material";
+pub const MATERIAL_BINDING: u32 = 1;
+
+// Buffer read by the uniform blocks of the shaders bound to the same binding point
+pub struct UniformBuffer {
+    gl: Rc<glow::Context>,
+    stats: Rc<StatsCounter>,
+    buffer: glow::Buffer,
+    size: usize
+}
+
+impl UniformBuffer {
+    pub fn new(render_command: &RenderCommand, size: usize) -> UniformBuffer {
+        let gl = render_command.gl().clone();
+
+        let buffer = unsafe {
+            let buffer = gl.create_buffer().expect("Failed to create uniform buffer");
+            gl.bind_buffer(glow::UNIFORM_BUFFER, Some(buffer));
+            gl.buffer_data_size(glow::UNIFORM_BUFFER, size as i32, glow::DYNAMIC_DRAW);
+            buffer
+        };
+
+        UniformBuffer { gl, stats: render_command.get_stats_counter().clone(), buffer, size }
+    }
+
+    // Writes the data starting at the offset in bytes, it must follow the std140 layout of the block
+    pub fn set_data<T: Pod>(&self, offset: usize, data: &[T]) {
+        let bytes = as_bytes(data);
+        if offset + bytes.len() > self.size {
+            hds_core_error!("Uniform buffer write of {} bytes at {} overflows its {} bytes", bytes.len(), offset, self.size);
+            return;
+        }
+
+        unsafe {
+            self.gl.bind_buffer(glow::UNIFORM_BUFFER, Some(self.buffer));
+            self.gl.buffer_sub_data_u8_slice(glow::UNIFORM_BUFFER, offset as i32, bytes);
+        }
+
+        self.stats.add_buffer_upload(bytes.len());
+    }
+
+    pub fn bind(&self, binding: u32) {
+        unsafe { self.gl.bind_buffer_base(glow::UNIFORM_BUFFER, binding, Some(self.buffer)) };
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Drop for UniformBuffer {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_buffer(self.buffer) };
+    }
+}
+
+// Data of the Camera block, the same for every draw of a scene:
+//
+// layout(std140) uniform Camera
+// {
+//     mat4 u_ViewProjection;
+//     vec4 u_CameraPosition;
+// };
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CameraData {
+    // Columns of the matrix
+    pub view_projection: [f32; 16],
+    // w is 1
+    pub position: [f32; 4]
+}
+
+// Only floats, without padding
+unsafe impl Zeroable for CameraData {}
+unsafe impl Pod for CameraData {}
+
+// Camera block written once per scene instead of once per shader
+pub struct CameraBuffer {
+    buffer: UniformBuffer
+}
+
+impl CameraBuffer {
+    pub fn new(render_command: &RenderCommand) -> CameraBuffer {
+        CameraBuffer { buffer: UniformBuffer::new(render_command, mem::size_of::<CameraData>()) }
+    }
+
+    // Writes the camera and binds the buffer
+    pub fn set_camera(&self, camera: &dyn Camera) {
+        let data = CameraData {
+            view_projection: camera.get_view_projection().to_cols_array(),
+            position: camera.get_position().extend(1.0).to_array()
+        };
+        self.buffer.set_data(0, &[data]);
+        self.bind();
+    }
+
+    pub fn bind(&self) {
+        self.buffer.bind(CAMERA_BINDING);
+    }
+}
@@ -0,0 +1,109 @@
+use std::rc::Rc;
+
+use glow::HasContext;
+
+use crate::logger::*;
+
+use super::{IndexBuffer, RenderCommand, ShaderDataType, VertexBuffer};
+
+// Vertex buffers with their attribute setup plus the index buffer used to draw them
+pub struct VertexArray {
+    gl: Rc<glow::Context>,
+    vertex_array: glow::VertexArray,
+    vertex_buffers: Vec<VertexBuffer>,
+    index_buffer: Option<IndexBuffer>,
+    // Next free attribute location
+    attribute_index: u32
+}
+
+impl VertexArray {
+    pub fn new(render_command: &RenderCommand) -> VertexArray {
+        let gl = render_command.gl().clone();
+        let vertex_array = unsafe { gl.create_vertex_array().expect("Failed to create vertex array") };
+
+        VertexArray { gl, vertex_array, vertex_buffers: vec![], index_buffer: None, attribute_index: 0 }
+    }
+
+    pub fn bind(&self) {
+        unsafe { self.gl.bind_vertex_array(Some(self.vertex_array)) };
+    }
+
+    pub fn unbind(&self) {
+        unsafe { self.gl.bind_vertex_array(None) };
+    }
+
+    // The attributes of the buffer take the locations after the ones of the previous buffers
+    pub fn add_vertex_buffer(&mut self, vertex_buffer: VertexBuffer) {
+        let layout = vertex_buffer.get_layout();
+        if layout.is_empty() {
+            hds_core_error!("Vertex buffer added to a vertex array without a layout");
+            return;
+        }
+
+        self.bind();
+        vertex_buffer.bind();
+
+        let stride = layout.get_stride() as i32;
+
+        for element in layout.get_elements() {
+            let data_type = element.get_data_type();
+            let count = data_type.component_count() as i32;
+
+            unsafe {
+                match data_type {
+                    ShaderDataType::Float | ShaderDataType::Float2 | ShaderDataType::Float3 | ShaderDataType::Float4 => {
+                        self.gl.enable_vertex_attrib_array(self.attribute_index);
+                        self.gl.vertex_attrib_pointer_f32(self.attribute_index, count, data_type.gl_base_type(),
+                            element.is_normalized(), stride, element.get_offset() as i32);
+                        self.attribute_index += 1;
+                    },
+                    ShaderDataType::Int | ShaderDataType::Int2 | ShaderDataType::Int3 | ShaderDataType::Int4 |
+                    ShaderDataType::Bool => {
+                        self.gl.enable_vertex_attrib_array(self.attribute_index);
+                        self.gl.vertex_attrib_pointer_i32(self.attribute_index, count, data_type.gl_base_type(),
+                            stride, element.get_offset() as i32);
+                        self.attribute_index += 1;
+                    },
+                    // A matrix takes one location per column
+                    ShaderDataType::Mat3 | ShaderDataType::Mat4 => {
+                        let column_size = count * 4;
+
+                        for column in 0..count {
+                            self.gl.enable_vertex_attrib_array(self.attribute_index);
+                            self.gl.vertex_attrib_pointer_f32(self.attribute_index, count, data_type.gl_base_type(),
+                                element.is_normalized(), stride, element.get_offset() as i32 + column * column_size);
+                            self.attribute_index += 1;
+                        }
+                    },
+                }
+            }
+        }
+
+        self.vertex_buffers.push(vertex_buffer);
+    }
+
+    pub fn set_index_buffer(&mut self, index_buffer: IndexBuffer) {
+        self.bind();
+        index_buffer.bind();
+
+        self.index_buffer = Some(index_buffer);
+    }
+
+    pub fn get_vertex_buffers(&self) -> &[VertexBuffer] {
+        &self.vertex_buffers
+    }
+
+    pub fn get_vertex_buffers_mut(&mut self) -> &mut [VertexBuffer] {
+        &mut self.vertex_buffers
+    }
+
+    pub fn get_index_buffer(&self) -> Option<&IndexBuffer> {
+        self.index_buffer.as_ref()
+    }
+}
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        unsafe { self.gl.delete_vertex_array(self.vertex_array) };
+    }
+}
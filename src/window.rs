@@ -2,16 +2,18 @@ use glutin::{
     config::ConfigTemplateBuilder,
     context::{ContextAttributesBuilder, NotCurrentGlContext, PossiblyCurrentContext},
     display::{GetGlDisplay, GlDisplay},
-    surface::{Surface, SurfaceAttributesBuilder, WindowSurface},
+    surface::{GlSurface, Surface, SurfaceAttributesBuilder, SwapInterval, WindowSurface},
 };
 
 use winit::{
-    window::{Window, WindowBuilder},
-    event_loop::EventLoop
+    dpi::{LogicalSize, PhysicalPosition},
+    window::{Fullscreen, Window, WindowBuilder},
+    event_loop::EventLoop,
+    monitor::{MonitorHandle, VideoMode}
 };
 
 use raw_window_handle::HasRawWindowHandle;
-use std::num::NonZeroU32;
+use std::{fs, io, num::NonZeroU32, path::{Path, PathBuf}};
 
 use crate::logger::*;
 
@@ -19,14 +21,30 @@ pub struct WindowData {
     title: &'static str,
     width: u32,
     height: u32,
-    vsync: bool
+    vsync: bool,
+    // Debug GL context, the driver messages go to the logger
+    debug_context: bool,
+    // Outer position of the window in physical pixels, none lets the platform decide
+    position: Option<(i32, i32)>,
+    maximized: bool,
+    fullscreen: bool,
+    // Video mode of the exclusive fullscreen, none is borderless
+    exclusive_mode: Option<VideoModeInfo>,
+    monitor: Option<String>,
+    // File where the window state is saved when the application closes
+    settings_path: Option<PathBuf>
 }
 
-impl WindowData {
-    pub fn default() -> WindowData {
-        WindowData {title: "Hades Engine", width: Self::default_width(), height: Self::default_height(), vsync: true}
+impl Default for WindowData {
+    fn default() -> WindowData {
+        WindowData {
+            title: "Hades Engine", width: Self::default_width(), height: Self::default_height(), vsync: true,
+            debug_context: cfg!(debug_assertions), position: None, maximized: false, fullscreen: false, exclusive_mode: None, monitor: None, settings_path: None
+        }
     }
+}
 
+impl WindowData {
     pub fn default_width() -> u32 {
         1280
     }
@@ -54,21 +72,307 @@ impl WindowData {
     pub fn is_vsync(&self) -> bool {
         self.vsync
     }
+
+    // Applied when the window is created
+    pub fn set_debug_context(&mut self, enabled: bool) {
+        self.debug_context = enabled
+    }
+
+    pub fn is_debug_context(&self) -> bool {
+        self.debug_context
+    }
+
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn get_position(&self) -> Option<(i32, i32)> {
+        self.position
+    }
+
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.position = Some((x, y))
+    }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        self.maximized = maximized
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        self.maximized
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    pub fn get_exclusive_mode(&self) -> Option<&VideoModeInfo> {
+        self.exclusive_mode.as_ref()
+    }
+
+    pub fn get_monitor(&self) -> Option<&str> {
+        self.monitor.as_deref()
+    }
+
+    pub fn set_monitor(&mut self, monitor: Option<String>) {
+        self.monitor = monitor
+    }
+
+    pub fn get_settings_path(&self) -> Option<&Path> {
+        self.settings_path.as_deref()
+    }
+
+    // Enables the persistence of the window state, restoring the previous session if the file exists
+    pub fn with_persistence(mut self, path: impl Into<PathBuf>) -> WindowData {
+        let path = path.into();
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                self.apply_settings(&contents);
+                hds_core_info!("Restored window state from {}", path.display());
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => hds_core_warn!("Failed to read window state {}: {}", path.display(), err),
+        }
+
+        self.settings_path = Some(path);
+        self
+    }
+
+    // Writes the window state to the settings file, does nothing if the persistence is disabled
+    pub fn save(&self) -> io::Result<()> {
+        let path = match &self.settings_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut contents = format!("width={}\nheight={}\nmaximized={}\nfullscreen={}\n", self.width, self.height, self.maximized, self.fullscreen);
+
+        if let Some((x, y)) = self.position {
+            contents.push_str(&format!("x={}\ny={}\n", x, y));
+        }
+
+        if let Some(mode) = &self.exclusive_mode {
+            contents.push_str(&format!("video_mode={}x{}x{}@{}\n", mode.width, mode.height, mode.bit_depth, mode.refresh_rate_millihertz));
+        }
+
+        if let Some(monitor) = &self.monitor {
+            contents.push_str(&format!("monitor={}\n", monitor));
+        }
+
+        fs::write(path, contents)
+    }
+
+    fn apply_settings(&mut self, contents: &str) {
+        let (mut x, mut y) = (None, None);
+
+        for line in contents.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => continue,
+            };
+
+            match key {
+                // A window can't be created with a zero size
+                "width" => match value.parse() {
+                    Ok(width) if width > 0 => self.width = width,
+                    _ => hds_core_warn!("Invalid saved window width {}", value),
+                },
+                "height" => match value.parse() {
+                    Ok(height) if height > 0 => self.height = height,
+                    _ => hds_core_warn!("Invalid saved window height {}", value),
+                },
+                "maximized" => self.maximized = value == "true",
+                "fullscreen" => self.fullscreen = value == "true",
+                "video_mode" => match VideoModeInfo::parse(value) {
+                    Some(mode) => self.exclusive_mode = Some(mode),
+                    None => hds_core_warn!("Invalid saved video mode {}", value),
+                },
+                "x" => x = value.parse().ok(),
+                "y" => y = value.parse().ok(),
+                "monitor" => self.monitor = Some(value.to_string()),
+                _ => hds_core_warn!("Unknown window setting {}", key),
+            }
+        }
+
+        if let (Some(x), Some(y)) = (x, y) {
+            self.position = Some((x, y));
+        }
+    }
+
+    // Drops the saved placement if the monitor where the window was is not connected anymore,
+    // and keeps the window inside the monitor when its bounds changed. Returns the monitor to use
+    fn validate_placement(&mut self, event_loop: &EventLoop<()>) -> Option<MonitorHandle> {
+        let saved = self.monitor.as_ref().and_then(|name| event_loop.available_monitors().find(|monitor| monitor.name().as_ref() == Some(name)));
+
+        if let (Some(name), None) = (&self.monitor, &saved) {
+            hds_core_warn!("Saved monitor {} is not available, using the primary monitor", name);
+            self.monitor = None;
+            self.position = None;
+            self.fullscreen = false;
+            self.exclusive_mode = None;
+        }
+
+        let monitor = saved.or_else(|| event_loop.primary_monitor()).or_else(|| event_loop.available_monitors().next());
+
+        if let (Some(monitor), Some((x, y))) = (&monitor, self.position) {
+            let origin = monitor.position();
+            let size = monitor.size();
+            let width = (self.width as f64 * monitor.scale_factor()) as i32;
+            let height = (self.height as f64 * monitor.scale_factor()) as i32;
+
+            // A window bigger than the monitor starts at its corner
+            let max_x = origin.x + (size.width as i32 - width).max(0);
+            let max_y = origin.y + (size.height as i32 - height).max(0);
+            self.position = Some((x.clamp(origin.x, max_x), y.clamp(origin.y, max_y)));
+        }
+
+        monitor
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct VideoModeInfo {
+    width: u32,
+    height: u32,
+    bit_depth: u16,
+    refresh_rate_millihertz: u32
+}
+
+impl VideoModeInfo {
+    fn from_mode(mode: &VideoMode) -> VideoModeInfo {
+        VideoModeInfo {
+            width: mode.size().width,
+            height: mode.size().height,
+            bit_depth: mode.bit_depth(),
+            refresh_rate_millihertz: mode.refresh_rate_millihertz()
+        }
+    }
+
+    // Reads the "1920x1080x32@60000" format of the window settings
+    fn parse(text: &str) -> Option<VideoModeInfo> {
+        let (size, refresh_rate) = text.split_once('@')?;
+        let mut parts = size.split('x');
+        let mode = VideoModeInfo {
+            width: parts.next()?.parse().ok()?,
+            height: parts.next()?.parse().ok()?,
+            bit_depth: parts.next()?.parse().ok()?,
+            refresh_rate_millihertz: refresh_rate.parse().ok()?
+        };
+
+        parts.next().is_none().then_some(mode)
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_bit_depth(&self) -> u16 {
+        self.bit_depth
+    }
+
+    pub fn get_refresh_rate(&self) -> f32 {
+        self.refresh_rate_millihertz as f32 / 1000.0
+    }
+}
+
+pub struct MonitorInfo {
+    index: usize,
+    name: String,
+    position: (i32, i32),
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+    primary: bool,
+    video_modes: Vec<VideoModeInfo>
+}
+
+impl MonitorInfo {
+    // Index used to select the monitor in the fullscreen functions
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+
+    pub fn get_video_modes(&self) -> &[VideoModeInfo] {
+        &self.video_modes
+    }
+}
+
+pub enum FullscreenMode {
+    Windowed,
+    // Borderless window covering the monitor, none uses the current monitor
+    Borderless { monitor: Option<usize> },
+    // Exclusive fullscreen changing the video mode of the monitor
+    Exclusive { monitor: usize, video_mode: usize }
 }
 
 pub struct WindowSystem;
 
 impl WindowSystem {
-    pub fn init_window(event_loop: &EventLoop<()>) -> (Window, Surface<WindowSurface>, PossiblyCurrentContext) {
-        let window_data = WindowData::default();
-        let window_builder = WindowBuilder::new()
+    pub fn init_window(event_loop: &EventLoop<()>, window_data: &mut WindowData) -> (Window, Surface<WindowSurface>, PossiblyCurrentContext) {
+        let monitor = window_data.validate_placement(event_loop);
+
+        let mut window_builder = WindowBuilder::new()
                     .with_title(window_data.get_title())
-                    .with_inner_size(winit::dpi::LogicalSize::new(window_data.get_width(), window_data.get_height()));
+                    .with_inner_size(LogicalSize::new(window_data.get_width(), window_data.get_height()))
+                    .with_maximized(window_data.is_maximized());
+
+        if let Some((x, y)) = window_data.get_position() {
+            window_builder = window_builder.with_position(PhysicalPosition::new(x, y));
+        }
+
+        if window_data.is_fullscreen() {
+            let fullscreen = match (window_data.get_exclusive_mode(), &monitor) {
+                (Some(saved), Some(handle)) => match handle.video_modes().find(|mode| VideoModeInfo::from_mode(mode) == *saved) {
+                    Some(mode) => Fullscreen::Exclusive(mode),
+                    None => {
+                        hds_core_warn!("Saved video mode {}x{} is not available, using borderless fullscreen", saved.width, saved.height);
+                        Fullscreen::Borderless(monitor)
+                    }
+                },
+                _ => Fullscreen::Borderless(monitor),
+            };
+
+            window_builder = window_builder.with_fullscreen(Some(fullscreen));
+        }
 
         // Create opengl window using glutin for setup
         let (window, cfg) = glutin_winit::DisplayBuilder::new()
             .with_window_builder(Some(window_builder))
-            .build(&event_loop, ConfigTemplateBuilder::new(), |mut configs| {
+            .build(event_loop, ConfigTemplateBuilder::new(), |mut configs| {
                 configs.next().unwrap()
             })
             .expect("Failed to create OpenGL window");
@@ -78,19 +382,22 @@ impl WindowSystem {
         hds_core_info!("Creating window {} ({}, {})", window_data.get_title(), window_data.get_width(), window_data.get_height());
 
         // Create context and surface
-        let ctx_attr = ContextAttributesBuilder::new().build(Some(window.raw_window_handle()));
+        let ctx_attr = ContextAttributesBuilder::new()
+            .with_debug(window_data.is_debug_context())
+            .build(Some(window.raw_window_handle()));
         let context = unsafe {
             cfg.display()
                 .create_context(&cfg, &ctx_attr)
                 .expect("Failed to create OpenGL context")
         };
 
+        let size = window.inner_size();
         let sur_attr = SurfaceAttributesBuilder::<WindowSurface>::new()
             .with_srgb(Some(true))
             .build(
                 window.raw_window_handle(), 
-                NonZeroU32::new(WindowData::default_width()).unwrap(), 
-                NonZeroU32::new(WindowData::default_height()).unwrap()
+                NonZeroU32::new(size.width.max(1)).unwrap(), 
+                NonZeroU32::new(size.height.max(1)).unwrap()
             );
         
         let surface = unsafe {
@@ -100,7 +407,103 @@ impl WindowSystem {
         };
 
         let context = context.make_current(&surface).expect("Failed to create OpenGL context");
+        Self::apply_vsync(&surface, &context, window_data.is_vsync());
 
         (window, surface, context)
     }
-}
\ No newline at end of file
+
+    pub fn apply_vsync(surface: &Surface<WindowSurface>, context: &PossiblyCurrentContext, enabled: bool) {
+        let interval = if enabled {
+            SwapInterval::Wait(NonZeroU32::new(1).unwrap())
+        }
+        else {
+            SwapInterval::DontWait
+        };
+
+        if let Err(err) = surface.set_swap_interval(context, interval) {
+            hds_core_warn!("Failed to change vsync: {}", err);
+        }
+    }
+
+    // Stores the current placement of the window, the size and position are only
+    // tracked while the window is not maximized or fullscreen so they can be restored
+    pub fn capture_state(window: &Window, window_data: &mut WindowData) {
+        window_data.set_maximized(window.is_maximized());
+        let fullscreen = window.fullscreen();
+        window_data.set_fullscreen(fullscreen.is_some());
+        window_data.exclusive_mode = match &fullscreen {
+            Some(Fullscreen::Exclusive(mode)) => Some(VideoModeInfo::from_mode(mode)),
+            _ => None,
+        };
+        window_data.set_monitor(window.current_monitor().and_then(|monitor| monitor.name()));
+
+        if window_data.is_maximized() || window_data.is_fullscreen() {
+            return;
+        }
+
+        let size: LogicalSize<u32> = window.inner_size().to_logical(window.scale_factor());
+        if size.width > 0 && size.height > 0 {
+            window_data.set_size(size.width, size.height);
+        }
+
+        if let Ok(position) = window.outer_position() {
+            window_data.set_position(position.x, position.y);
+        }
+    }
+
+    pub fn get_monitors(window: &Window) -> Vec<MonitorInfo> {
+        let primary = window.primary_monitor();
+
+        window.available_monitors().enumerate().map(|(index, monitor)| {
+            let position = monitor.position();
+            let size = monitor.size();
+
+            MonitorInfo {
+                index,
+                name: monitor.name().unwrap_or_else(|| format!("Monitor {}", index)),
+                position: (position.x, position.y),
+                width: size.width,
+                height: size.height,
+                scale_factor: monitor.scale_factor(),
+                primary: primary.as_ref() == Some(&monitor),
+                video_modes: monitor.video_modes().map(|mode| VideoModeInfo::from_mode(&mode)).collect()
+            }
+        }).collect()
+    }
+
+    pub fn set_fullscreen(window: &Window, window_data: &mut WindowData, mode: FullscreenMode) {
+        let fullscreen = match mode {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless { monitor } => {
+                let monitor = match monitor {
+                    Some(index) => match window.available_monitors().nth(index) {
+                        Some(monitor) => Some(monitor),
+                        None => {
+                            hds_core_error!("Monitor {} is not available", index);
+                            return;
+                        }
+                    },
+                    None => None,
+                };
+
+                Some(Fullscreen::Borderless(monitor))
+            },
+            FullscreenMode::Exclusive { monitor, video_mode } => {
+                let mode = window.available_monitors()
+                    .nth(monitor)
+                    .and_then(|monitor| monitor.video_modes().nth(video_mode));
+
+                match mode {
+                    Some(mode) => Some(Fullscreen::Exclusive(mode)),
+                    None => {
+                        hds_core_error!("Video mode {} of monitor {} is not available", video_mode, monitor);
+                        return;
+                    }
+                }
+            }
+        };
+
+        window.set_fullscreen(fullscreen);
+        Self::capture_state(window, window_data);
+    }
+}